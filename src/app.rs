@@ -1,12 +1,48 @@
-use crate::notes::{load_notes, Note};
+use crate::calendar::day_marks;
+use crate::config::{load_config, Config};
+use crate::editor::{Editor, LineEditor};
+use crate::graph::Graph;
+use crate::index::SearchIndex;
+use crate::links::{Backlink, LinkIndex};
+use crate::notes::{load_notes, sort_notes, Note};
+use crate::query::{parse_filter, Filter};
+use crate::replace::ReplaceHit;
+use crate::search::{SearchMode, SearchResult};
+use crate::templates::Template;
+use crate::tree::{build_tree, is_in_folder, parent_folder, TreeRow};
+use chrono::{Local, NaiveDate};
+use regex::Regex;
+use std::collections::HashSet;
 
 pub enum Mode {
     Normal,
+    Insert,
+    SelectTemplate,
+    Capture,
+    Rename,
+    SelectTag,
+    Move,
+    Graph,
+    Calendar,
+    Find,
+    Search,
+    NoteSearch,
+    SaveSearch,
+    Replace,
+    ReplacePreview,
 }
 
 pub enum Focus {
     Left,
     Right,
+    Backlinks,
+}
+
+#[derive(PartialEq)]
+pub enum CaptureTarget {
+    NewNote,
+    SelectedNote,
+    Inbox,
 }
 
 #[derive(PartialEq)]
@@ -17,7 +53,9 @@ pub enum SelectedButton {
 
 pub struct App {
     pub notes: Vec<Note>,
-    pub selected_note: usize,
+    /// Id of the selected note; stays on the same note when the list is
+    /// filtered or reordered.
+    pub selected_id: u64,
     pub note_scroll: u16,
     pub list_scroll: usize,
     pub mode: Mode,
@@ -26,13 +64,84 @@ pub struct App {
     pub selected_line: usize,
     pub confirm_delete: bool,
     pub selected_button: SelectedButton,
+    pub editor: Option<Editor>,
+    pub templates: Vec<Template>,
+    pub selected_template: usize,
+    pub config: Config,
+    pub input: LineEditor,
+    pub capture_target: CaptureTarget,
+    /// Shown instead of the shortcuts until the next key press.
+    pub status: Option<String>,
+    pub tag_filter: Option<String>,
+    pub tag_list: Vec<(String, usize)>,
+    pub selected_tag: usize,
+    pub selected_folder: Option<String>,
+    /// Saved search the cursor is on.
+    pub selected_search: Option<usize>,
+    /// Saved search the notes list is filtered by.
+    pub active_search: Option<usize>,
+    /// Parsed queries of the saved searches, in the same order.
+    pub saved_filters: Vec<Result<Filter, String>>,
+    /// Number of notes matching each saved search.
+    pub saved_counts: Vec<usize>,
+    /// Query waiting for a name before it is saved.
+    pub pending_query: String,
+    pub replace_input: LineEditor,
+    /// Whether the replace prompt edits the replacement instead of the
+    /// pattern.
+    pub editing_replacement: bool,
+    pub replace_regex: bool,
+    pub replace_hits: Vec<ReplaceHit>,
+    pub selected_hit: usize,
+    pub collapsed_folders: HashSet<String>,
+    /// Whether the notes list shows the archive instead of the active notes.
+    pub show_archived: bool,
+    /// Ids of the notes left by following links, most recent last.
+    pub back_stack: Vec<u64>,
+    pub links: LinkIndex,
+    pub search_index: SearchIndex,
+    pub show_backlinks: bool,
+    pub selected_backlink: usize,
+    /// Ids of the note the graph view is centered on and the highlighted one.
+    pub graph_center: u64,
+    pub graph_selected: u64,
+    pub graph: Option<Graph>,
+    /// Only notes with something happening on this day are listed.
+    pub date_filter: Option<NaiveDate>,
+    /// Day under the cursor in the calendar.
+    pub calendar_date: NaiveDate,
+    pub selected_match: usize,
+    pub search_mode: SearchMode,
+    pub search_results: Vec<SearchResult>,
+    /// Mode of the search within the selected note.
+    pub note_search_mode: SearchMode,
+    pub search_error: Option<String>,
+    /// Matches of the last search, highlighted in the preview.
+    pub highlight: Option<Regex>,
 }
 
 impl App {
     pub fn new() -> Self {
-        App {
-            notes: load_notes(),
-            selected_note: 0,
+        let config = load_config();
+        let notes = load_notes(config.title_source);
+        let mut app = App {
+            links: LinkIndex::build(&notes),
+            search_index: SearchIndex::load(&notes),
+            show_backlinks: false,
+            selected_backlink: 0,
+            graph_center: 0,
+            graph_selected: 0,
+            graph: None,
+            date_filter: None,
+            calendar_date: Local::now().date_naive(),
+            selected_match: 0,
+            search_mode: SearchMode::Ranked,
+            note_search_mode: SearchMode::IgnoreCase,
+            search_results: Vec::new(),
+            search_error: None,
+            highlight: None,
+            notes,
+            selected_id: 0,
             note_scroll: 0,
             list_scroll: 0,
             mode: Mode::Normal,
@@ -41,6 +150,309 @@ impl App {
             selected_line: 0,
             confirm_delete: false,
             selected_button: SelectedButton::No,
+            editor: None,
+            templates: Vec::new(),
+            selected_template: 0,
+            config,
+            input: LineEditor::default(),
+            capture_target: CaptureTarget::NewNote,
+            status: None,
+            tag_filter: None,
+            tag_list: Vec::new(),
+            selected_tag: 0,
+            selected_folder: None,
+            selected_search: None,
+            active_search: None,
+            saved_filters: Vec::new(),
+            saved_counts: Vec::new(),
+            pending_query: String::new(),
+            replace_input: LineEditor::default(),
+            editing_replacement: false,
+            replace_regex: false,
+            replace_hits: Vec::new(),
+            selected_hit: 0,
+            collapsed_folders: HashSet::new(),
+            show_archived: false,
+            back_stack: Vec::new(),
+        };
+        app.parse_saved_searches();
+        // Select the first visible note before anything is drawn.
+        app.sync_selection();
+        app
+    }
+
+    /// Indexes into `notes` of the entries shown in the notes list, in the
+    /// active sort order.
+    pub fn visible_notes(&self) -> Vec<usize> {
+        let search = self.active_search_filter();
+        let mut visible: Vec<usize> = self
+            .notes
+            .iter()
+            .enumerate()
+            // A saved search decides on its own whether archived notes match.
+            .filter(|(_, note)| match &search {
+                Some(filter) => filter.matches(note),
+                None => note.archived == self.show_archived,
+            })
+            .filter(|(_, note)| match &self.tag_filter {
+                Some(tag) => note.tags.contains(tag),
+                None => true,
+            })
+            .filter(|(_, note)| match self.date_filter {
+                Some(date) => day_marks(note, date).any(),
+                None => true,
+            })
+            .map(|(i, _)| i)
+            .collect();
+        sort_notes(&self.notes, &mut visible, self.config.sort_mode);
+        visible
+    }
+
+    fn active_search_filter(&self) -> Option<&Filter> {
+        self.saved_filters.get(self.active_search?)?.as_ref().ok()
+    }
+
+    /// Parses the saved searches after they were loaded or changed.
+    pub fn parse_saved_searches(&mut self) {
+        self.saved_filters = self
+            .config
+            .saved_searches
+            .iter()
+            .map(|search| parse_filter(&search.query))
+            .collect();
+        self.count_saved_searches();
+    }
+
+    /// Counts the notes matching each saved search after notes changed.
+    pub fn count_saved_searches(&mut self) {
+        self.saved_counts = self
+            .saved_filters
+            .iter()
+            .map(|filter| match filter {
+                Ok(filter) => self.notes.iter().filter(|n| filter.matches(n)).count(),
+                Err(_) => 0,
+            })
+            .collect();
+    }
+
+    pub fn index_of(&self, id: u64) -> Option<usize> {
+        self.notes.iter().position(|note| note.id == id)
+    }
+
+    /// Appends a note with a fresh id and returns its index.
+    pub fn add_note(&mut self, mut note: Note) -> usize {
+        note.id = self.notes.iter().map(|n| n.id).max().unwrap_or(0) + 1;
+        self.links.update(&note);
+        self.search_index.update(&note);
+        self.notes.push(note);
+        self.count_saved_searches();
+        self.notes.len() - 1
+    }
+
+    pub fn remove_note(&mut self, index: usize) -> Note {
+        let note = self.notes.remove(index);
+        self.links.remove(note.id);
+        self.search_index.remove(note.id);
+        self.count_saved_searches();
+        note
+    }
+
+    /// Refreshes the indexes after the content of a note changed.
+    pub fn note_changed(&mut self, index: usize) {
+        self.links.update(&self.notes[index]);
+        self.search_index.update(&self.notes[index]);
+        self.count_saved_searches();
+    }
+
+    /// Notes linking to the selected note.
+    pub fn backlinks(&self) -> Vec<Backlink> {
+        match self.current_note() {
+            Some(index) => self.links.backlinks(&self.notes[index]),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn select_note(&mut self, index: usize) {
+        self.selected_folder = None;
+        self.selected_search = None;
+        self.selected_id = self.notes[index].id;
+        self.note_scroll = 0;
+        self.selected_line = 0;
+        self.selected_backlink = 0;
+        self.highlight = None;
+    }
+
+    pub fn tree_rows(&self) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+        if !self.show_archived {
+            for (position, search) in self.config.saved_searches.iter().enumerate() {
+                let count = match &self.saved_filters[position] {
+                    Ok(_) => Ok(self.saved_counts[position]),
+                    Err(err) => Err(err.clone()),
+                };
+                rows.push(TreeRow::Search {
+                    position,
+                    name: search.name.clone(),
+                    count,
+                    active: self.active_search == Some(position),
+                });
+            }
+        }
+        rows.extend(build_tree(
+            &self.notes,
+            &self.visible_notes(),
+            &self.collapsed_folders,
+        ));
+        rows
+    }
+
+    /// Selects a note, switching views and dropping filters that would
+    /// hide it.
+    pub fn open_note(&mut self, index: usize) {
+        let note = &self.notes[index];
+        self.show_archived = note.archived;
+        if let Some(tag) = &self.tag_filter {
+            if !note.tags.contains(tag) {
+                self.tag_filter = None;
+            }
+        }
+        if let Some(date) = self.date_filter {
+            if !day_marks(note, date).any() {
+                self.date_filter = None;
+            }
+        }
+        if let Some(filter) = self.active_search_filter() {
+            if !filter.matches(note) {
+                self.active_search = None;
+            }
+        }
+        self.select_note(index);
+        self.reveal_note(index);
+    }
+
+    /// Moves the selection off the note at `index` to the next visible note,
+    /// or the previous one when it is the last.
+    pub fn select_neighbour(&mut self, index: usize) {
+        let visible = self.visible_notes();
+        if let Some(position) = visible.iter().position(|&i| i == index) {
+            let neighbour = visible
+                .get(position + 1)
+                .or(position.checked_sub(1).and_then(|p| visible.get(p)));
+            if let Some(&neighbour) = neighbour {
+                self.selected_id = self.notes[neighbour].id;
+            }
+        }
+    }
+
+    /// Row of the notes tree the cursor is on.
+    pub fn selected_row(&self, rows: &[TreeRow]) -> usize {
+        rows.iter()
+            .position(|row| match row {
+                TreeRow::Search { position, .. } => self.selected_search == Some(*position),
+                TreeRow::Folder { path, .. } => self.selected_folder.as_ref() == Some(path),
+                TreeRow::Note { index, .. } => {
+                    self.selected_folder.is_none()
+                        && self.selected_search.is_none()
+                        && self.notes[*index].id == self.selected_id
+                }
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn select_row(&mut self, row: &TreeRow) {
+        match row {
+            TreeRow::Search { position, .. } => {
+                self.selected_folder = None;
+                self.selected_search = Some(*position);
+                self.note_scroll = 0;
+                self.selected_line = 0;
+            }
+            TreeRow::Folder { path, .. } => {
+                self.selected_search = None;
+                self.selected_folder = Some(path.clone());
+                self.note_scroll = 0;
+                self.selected_line = 0;
+            }
+            TreeRow::Note { index, .. } => self.select_note(*index),
+        }
+    }
+
+    /// Index of the selected note, or `None` when the cursor is on a folder
+    /// or saved search.
+    pub fn current_note(&self) -> Option<usize> {
+        if self.selected_folder.is_some() || self.selected_search.is_some() {
+            None
+        } else {
+            self.index_of(self.selected_id)
+        }
+    }
+
+    /// Folder new notes are created in: the selected folder, or the folder
+    /// of the selected note.
+    pub fn current_folder(&self) -> String {
+        match (&self.selected_folder, self.current_note()) {
+            (Some(folder), _) => folder.clone(),
+            (None, Some(index)) => self.notes[index].folder.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Expands every folder containing the note so it shows up in the tree.
+    pub fn reveal_note(&mut self, index: usize) {
+        if self.notes[index].is_pinned() {
+            return;
+        }
+        let mut folder = self.notes[index].folder.as_str();
+        while let Some(parent) = parent_folder(folder) {
+            self.collapsed_folders.remove(folder);
+            folder = parent;
+        }
+    }
+
+    /// Keeps the selection on a visible note, dropping a filter that no
+    /// longer matches anything.
+    pub fn sync_selection(&mut self) {
+        // Selecting another note resets this, but the selected note may
+        // have lost backlinks.
+        let backlinks = self.backlinks().len();
+        self.selected_backlink = self.selected_backlink.min(backlinks.saturating_sub(1));
+        let searches = self.config.saved_searches.len();
+        self.active_search = self.active_search.filter(|&i| i < searches);
+        self.selected_search = self
+            .selected_search
+            .filter(|&i| i < searches && !self.show_archived);
+        let mut visible = self.visible_notes();
+        if visible.is_empty() && (self.tag_filter.is_some() || self.date_filter.is_some()) {
+            self.tag_filter = None;
+            self.date_filter = None;
+            visible = self.visible_notes();
+        }
+        if let Some(folder) = &self.selected_folder {
+            let exists = visible.iter().any(|&i| {
+                !self.notes[i].is_pinned() && is_in_folder(&self.notes[i].folder, folder)
+            });
+            if !exists {
+                self.selected_folder = None;
+            }
+        }
+        if self.selected_folder.is_some() || self.selected_search.is_some() {
+            return;
+        }
+        match self.index_of(self.selected_id) {
+            Some(index) if visible.contains(&index) => self.reveal_note(index),
+            _ => match visible.first() {
+                Some(&first) => {
+                    self.select_note(first);
+                    self.reveal_note(first);
+                    self.list_scroll = 0;
+                }
+                None => {
+                    self.selected_id = 0;
+                    if searches > 0 && !self.show_archived {
+                        self.selected_search = self.active_search.or(Some(0));
+                    }
+                }
+            },
         }
     }
 }
@@ -0,0 +1,59 @@
+use crate::notes::Note;
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+
+/// What happened on a day of the calendar.
+#[derive(Default, Clone, Copy)]
+pub struct DayMarks {
+    pub journal: bool,
+    pub due: bool,
+    /// A note was created or modified.
+    pub activity: bool,
+}
+
+impl DayMarks {
+    pub fn any(&self) -> bool {
+        self.journal || self.due || self.activity
+    }
+}
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    DateTime::from_timestamp(timestamp, 0).map(|d| d.with_timezone(&Local).date_naive())
+}
+
+pub fn day_marks(note: &Note, date: NaiveDate) -> DayMarks {
+    DayMarks {
+        journal: note.journal_date == Some(date),
+        due: note.meta.due == Some(date),
+        activity: local_date(note.created) == Some(date) || local_date(note.modified) == Some(date),
+    }
+}
+
+/// Marks for every day of the month `date` falls in, indexed by day - 1.
+pub fn month_marks(notes: &[Note], date: NaiveDate) -> Vec<DayMarks> {
+    let mut marks = vec![DayMarks::default(); days_in_month(date) as usize];
+    let first = first_of_month(date);
+    let mut mark = |day: Option<NaiveDate>, apply: fn(&mut DayMarks)| {
+        if let Some(day) = day.filter(|day| first_of_month(*day) == first) {
+            apply(&mut marks[day.day0() as usize]);
+        }
+    };
+    for note in notes.iter().filter(|note| !note.archived) {
+        mark(note.journal_date, |m| m.journal = true);
+        mark(note.meta.due, |m| m.due = true);
+        mark(local_date(note.created), |m| m.activity = true);
+        mark(local_date(note.modified), |m| m.activity = true);
+    }
+    marks
+}
+
+pub fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+pub fn days_in_month(date: NaiveDate) -> u32 {
+    let first = first_of_month(date);
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(first);
+    next.signed_duration_since(first).num_days() as u32
+}
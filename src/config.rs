@@ -0,0 +1,102 @@
+use crate::utils::data_dir;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Decides which title is shown when a note has been renamed explicitly.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleSource {
+    /// The explicit title wins; the derived one is used until a note is renamed.
+    Explicit,
+    /// The title derived from the content wins; the explicit one is only used
+    /// when nothing can be derived.
+    Derived,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
+    Manual,
+    Title,
+    Modified,
+    Created,
+    OpenTodos,
+    Completion,
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Manual => SortMode::Title,
+            SortMode::Title => SortMode::Modified,
+            SortMode::Modified => SortMode::Created,
+            SortMode::Created => SortMode::OpenTodos,
+            SortMode::OpenTodos => SortMode::Completion,
+            SortMode::Completion => SortMode::Manual,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual",
+            SortMode::Title => "title A-Z",
+            SortMode::Modified => "last modified",
+            SortMode::Created => "created",
+            SortMode::OpenTodos => "open todos",
+            SortMode::Completion => "completion",
+        }
+    }
+}
+
+/// A named filter query shown as a smart list in the notes tree.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Title of the note that quick-capture items are appended to.
+    pub inbox_title: String,
+    pub title_source: TitleSource,
+    pub sort_mode: SortMode,
+    /// How many links away from the center the graph view reaches.
+    pub graph_hops: usize,
+    /// Folder daily notes are grouped into, one subfolder per month.
+    pub journal_folder: String,
+    pub saved_searches: Vec<SavedSearch>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            inbox_title: "Inbox".to_string(),
+            title_source: TitleSource::Explicit,
+            sort_mode: SortMode::Manual,
+            graph_hops: 2,
+            journal_folder: "journal".to_string(),
+            saved_searches: Vec::new(),
+        }
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    data_dir().join("config.json")
+}
+
+pub fn load_config() -> Config {
+    if let Ok(data) = std::fs::read_to_string(config_path()) {
+        if let Ok(config) = serde_json::from_str(&data) {
+            return config;
+        }
+    }
+    Config::default()
+}
+
+pub fn save_config(config: &Config) {
+    if let Ok(data) = serde_json::to_string_pretty(config) {
+        let _ = std::fs::write(config_path(), data);
+    }
+}
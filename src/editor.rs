@@ -0,0 +1,206 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use std::cell::Cell;
+
+pub struct Editor {
+    pub lines: Vec<String>,
+    pub row: usize,
+    pub col: usize,
+    pub scroll: Cell<usize>,
+    /// `"\r\n"` when the content used CRLF line endings.
+    line_ending: &'static str,
+    trailing_newline: bool,
+}
+
+impl Editor {
+    pub fn new(content: &str) -> Self {
+        let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Editor {
+            lines,
+            row: 0,
+            col: 0,
+            scroll: Cell::new(0),
+            line_ending: if content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+            trailing_newline: content.ends_with('\n'),
+        }
+    }
+
+    /// The edited text, with the line endings and trailing newline of the
+    /// original content.
+    pub fn content(&self) -> String {
+        let mut content = self.lines.join(self.line_ending);
+        if self.trailing_newline {
+            content.push_str(self.line_ending);
+        }
+        content
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Tab => {
+                for _ in 0..4 {
+                    self.insert_char(' ');
+                }
+            }
+            KeyCode::Enter => self.insert_newline(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(),
+            _ => {}
+        }
+    }
+
+    /// Returns the viewport's first line, scrolled just enough to keep the
+    /// cursor visible within `height` rows.
+    pub fn visible_start(&self, height: usize) -> usize {
+        let mut scroll = self.scroll.get();
+        if self.row < scroll {
+            scroll = self.row;
+        } else if height > 0 && self.row >= scroll + height {
+            scroll = self.row + 1 - height;
+        }
+        self.scroll.set(scroll);
+        scroll
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_col(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map(|(i, _)| i)
+            .unwrap_or(line.len())
+    }
+
+    fn insert_char(&mut self, c: char) {
+        let idx = self.byte_col();
+        self.lines[self.row].insert(idx, c);
+        self.col += 1;
+    }
+
+    fn insert_newline(&mut self) {
+        let idx = self.byte_col();
+        let rest = self.lines[self.row].split_off(idx);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.col = 0;
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let idx = self.byte_col();
+            self.lines[self.row].remove(idx);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < self.line_len() {
+            let idx = self.byte_col();
+            self.lines[self.row].remove(idx);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len();
+        }
+    }
+
+    fn move_right(&mut self) {
+        if self.col < self.line_len() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len());
+        }
+    }
+
+    fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len());
+        }
+    }
+}
+
+/// A single-line text input used by the prompt modals.
+#[derive(Default)]
+pub struct LineEditor {
+    pub value: String,
+    pub cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new(value: &str) -> Self {
+        LineEditor {
+            value: value.to_string(),
+            cursor: value.chars().count(),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(c) => {
+                let idx = self.byte_cursor();
+                self.value.insert(idx, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let idx = self.byte_cursor();
+                self.value.remove(idx);
+            }
+            KeyCode::Delete if self.cursor < self.value.chars().count() => {
+                let idx = self.byte_cursor();
+                self.value.remove(idx);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.value.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.chars().count(),
+            _ => {}
+        }
+    }
+
+    fn byte_cursor(&self) -> usize {
+        self.value
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+
+#[derive(Clone, Default)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    pub due: Option<NaiveDate>,
+    pub pinned: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RawFrontMatter {
+    title: Option<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    tags: Vec<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    aliases: Vec<String>,
+    due: Option<String>,
+    pinned: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Accepts both `tags: [a, b]` and `tags: a, b`.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let values = match Option::<OneOrMany>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(OneOrMany::One(value)) => value.split(',').map(|v| v.to_string()).collect(),
+        Some(OneOrMany::Many(values)) => values,
+    };
+    Ok(values
+        .iter()
+        .map(|v| v.trim().trim_start_matches('#').to_string())
+        .filter(|v| !v.is_empty())
+        .collect())
+}
+
+/// Splits a note into its front matter and the remaining body.
+///
+/// A YAML block is delimited by `---` lines (closed by `---` or `...`), a
+/// TOML block by `+++` lines. Blocks that fail to parse are treated as
+/// regular content.
+pub fn split_front_matter(content: &str) -> (Option<FrontMatter>, &str) {
+    let (delimiter, closers): (&str, &[&str]) = if content.starts_with("---") {
+        ("---", &["---", "..."])
+    } else if content.starts_with("+++") {
+        ("+++", &["+++"])
+    } else {
+        return (None, content);
+    };
+
+    let mut lines = content.split_inclusive('\n');
+    let block_start = match lines.next() {
+        Some(first) if first.trim_end() == delimiter => first.len(),
+        _ => return (None, content),
+    };
+
+    let mut offset = block_start;
+    for line in lines {
+        if closers.contains(&line.trim_end()) {
+            let block = &content[block_start..offset];
+            let body = &content[offset + line.len()..];
+            let raw = if delimiter == "---" {
+                parse_yaml(block)
+            } else {
+                parse_toml(block)
+            };
+            return match raw {
+                Some(raw) => (Some(raw.into()), body),
+                None => (None, content),
+            };
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+pub fn parse_front_matter(content: &str) -> Option<FrontMatter> {
+    split_front_matter(content).0
+}
+
+fn parse_yaml(block: &str) -> Option<RawFrontMatter> {
+    if block.trim().is_empty() {
+        return Some(RawFrontMatter::default());
+    }
+    serde_yaml::from_str(block).ok()
+}
+
+fn parse_toml(block: &str) -> Option<RawFrontMatter> {
+    let mut table: toml::Table = toml::from_str(block).ok()?;
+    // Unquoted TOML dates are parsed as datetimes, not strings.
+    if let Some(toml::Value::Datetime(due)) = table.get("due") {
+        let due = due.to_string();
+        table.insert("due".to_string(), toml::Value::String(due));
+    }
+    toml::Value::Table(table).try_into().ok()
+}
+
+impl From<RawFrontMatter> for FrontMatter {
+    fn from(raw: RawFrontMatter) -> Self {
+        FrontMatter {
+            title: raw
+                .title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+            tags: raw.tags,
+            aliases: raw.aliases,
+            due: raw
+                .due
+                .and_then(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok()),
+            pinned: raw.pinned,
+        }
+    }
+}
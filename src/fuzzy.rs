@@ -0,0 +1,63 @@
+use crate::app::App;
+
+/// Matches `query` as a case-insensitive subsequence of `text`. Returns a
+/// score, higher is better, and the char positions of the matched
+/// characters.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let text: Vec<char> = text.chars().collect();
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut from = 0;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase().next()?;
+        let offset = text[from..]
+            .iter()
+            .position(|c| c.to_lowercase().next() == Some(q))?;
+        let pos = from + offset;
+        score += 1;
+        if positions.last().is_some_and(|&last| last + 1 == pos) {
+            score += 5;
+        }
+        if pos == 0 || !text[pos - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= offset as i64;
+        positions.push(pos);
+        from = pos + 1;
+    }
+    Some((score, positions))
+}
+
+/// Notes in the current view whose title matches the finder query, best
+/// first, with the matched positions in the title.
+pub fn find_matches(app: &App) -> Vec<(usize, Vec<usize>)> {
+    let mut matches: Vec<(i64, usize, Vec<usize>)> = app
+        .notes
+        .iter()
+        .enumerate()
+        .filter(|(_, note)| note.archived == app.show_archived)
+        .filter_map(|(index, note)| {
+            fuzzy_match(&app.input.value, &note.title)
+                .map(|(score, positions)| (score, index, positions))
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.0.cmp(&a.0).then_with(|| {
+            app.notes[a.1]
+                .title
+                .to_lowercase()
+                .cmp(&app.notes[b.1].title.to_lowercase())
+        })
+    });
+    matches
+        .into_iter()
+        .map(|(_, index, positions)| (index, positions))
+        .collect()
+}
+
+/// Selects the highlighted finder result.
+pub fn open_match(app: &mut App) {
+    if let Some((index, _)) = find_matches(app).get(app.selected_match) {
+        app.open_note(*index);
+    }
+}
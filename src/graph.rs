@@ -0,0 +1,185 @@
+use crate::app::{App, Mode};
+use crate::links::LinkIndex;
+use crate::notes::Note;
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::TAU;
+
+pub struct GraphNode {
+    /// Index into `notes`.
+    pub index: usize,
+    /// Number of links between this note and the center.
+    pub depth: usize,
+    /// Position in the unit square around the center, `-1.0..=1.0`.
+    pub x: f64,
+    pub y: f64,
+}
+
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    /// Links as pairs of positions in `nodes`, from source to target.
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Note indexes by lowercased title and alias, the first note winning
+/// like in `resolve_link`.
+fn link_targets(notes: &[Note]) -> HashMap<String, usize> {
+    let mut targets = HashMap::new();
+    for (index, note) in notes.iter().enumerate() {
+        let names = std::iter::once(&note.title).chain(&note.meta.aliases);
+        for name in names {
+            targets
+                .entry(name.trim().to_ascii_lowercase())
+                .or_insert(index);
+        }
+    }
+    targets
+}
+
+/// Links of a note resolved to note indexes, skipping missing targets.
+fn outgoing(
+    notes: &[Note],
+    links: &LinkIndex,
+    targets: &HashMap<String, usize>,
+    index: usize,
+) -> Vec<usize> {
+    links
+        .targets(notes[index].id)
+        .iter()
+        .filter_map(|target| targets.get(&target.trim().to_ascii_lowercase()).copied())
+        .filter(|&target| target != index)
+        .collect()
+}
+
+/// Collects the notes within `hops` links of `center`, following links in
+/// both directions, and lays them out in rings by distance.
+pub fn build_graph(notes: &[Note], links: &LinkIndex, center: usize, hops: usize) -> Graph {
+    let ids: HashMap<u64, usize> = notes
+        .iter()
+        .enumerate()
+        .map(|(index, note)| (note.id, index))
+        .collect();
+    let targets = link_targets(notes);
+    let mut depths: Vec<Option<usize>> = vec![None; notes.len()];
+    let mut order = vec![center];
+    let mut queue = VecDeque::from([center]);
+    depths[center] = Some(0);
+
+    while let Some(index) = queue.pop_front() {
+        let depth = depths[index].unwrap_or(0);
+        if depth == hops {
+            continue;
+        }
+        let backlinks = links
+            .backlinks(&notes[index])
+            .into_iter()
+            .filter_map(|backlink| ids.get(&backlink.source).copied());
+        for next in outgoing(notes, links, &targets, index)
+            .into_iter()
+            .chain(backlinks)
+        {
+            if depths[next].is_none() && !notes[next].archived {
+                depths[next] = Some(depth + 1);
+                order.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let max_depth = order.iter().filter_map(|&i| depths[i]).max().unwrap_or(0);
+    let mut nodes = Vec::new();
+    for depth in 0..=max_depth {
+        let ring: Vec<usize> = order
+            .iter()
+            .copied()
+            .filter(|&i| depths[i] == Some(depth))
+            .collect();
+        let radius = depth as f64 / max_depth.max(1) as f64;
+        for (i, &index) in ring.iter().enumerate() {
+            // Offset every ring a little so nodes of neighbouring rings
+            // do not line up on the same spoke.
+            let angle = TAU * i as f64 / ring.len() as f64 + depth as f64 * 0.5;
+            nodes.push(GraphNode {
+                index,
+                depth,
+                x: radius * angle.cos(),
+                y: radius * angle.sin(),
+            });
+        }
+    }
+
+    let positions: HashMap<usize, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(position, node)| (node.index, position))
+        .collect();
+    let mut edges = Vec::new();
+    for (from, node) in nodes.iter().enumerate() {
+        for target in outgoing(notes, links, &targets, node.index) {
+            if let Some(&to) = positions.get(&target) {
+                if !edges.contains(&(from, to)) {
+                    edges.push((from, to));
+                }
+            }
+        }
+    }
+
+    Graph { nodes, edges }
+}
+
+impl Graph {
+    pub fn position_of(&self, index: usize) -> Option<usize> {
+        self.nodes.iter().position(|node| node.index == index)
+    }
+
+    /// Closest node from `from` roughly in the direction `(dx, dy)`.
+    pub fn neighbour_in_direction(&self, from: usize, dx: f64, dy: f64) -> Option<usize> {
+        let origin = &self.nodes[from];
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != from)
+            .filter_map(|(i, node)| {
+                let (x, y) = (node.x - origin.x, node.y - origin.y);
+                let along = x * dx + y * dy;
+                let across = (x * dy - y * dx).abs();
+                // Only nodes within 45 degrees of the direction qualify.
+                (along > 0.0 && across <= along).then_some((i, along + across * 2.0))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+}
+
+/// Rebuilds the graph around the note the graph view is centered on.
+/// Notes cannot change while the view is open, so the graph is only built
+/// when it opens, is re-centered or changes depth.
+pub fn refresh_graph(app: &mut App) {
+    app.graph = app
+        .index_of(app.graph_center)
+        .map(|center| build_graph(&app.notes, &app.links, center, app.config.graph_hops));
+}
+
+/// Moves the graph highlight to the nearest node in a direction.
+pub fn move_graph_selection(app: &mut App, dx: f64, dy: f64) {
+    let Some(graph) = &app.graph else {
+        return;
+    };
+    let from = app
+        .index_of(app.graph_selected)
+        .and_then(|index| graph.position_of(index))
+        .unwrap_or(0);
+    if let Some(to) = graph.neighbour_in_direction(from, dx, dy) {
+        app.graph_selected = app.notes[graph.nodes[to].index].id;
+    }
+}
+
+/// Leaves the graph view with the highlighted note selected.
+pub fn open_graph_selection(app: &mut App) {
+    if let Some(index) = app.index_of(app.graph_selected) {
+        if app.graph_selected != app.selected_id {
+            app.back_stack.push(app.selected_id);
+        }
+        app.open_note(index);
+    }
+    app.mode = Mode::Normal;
+}
@@ -0,0 +1,293 @@
+use crate::notes::Note;
+use crate::utils::data_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+#[derive(Serialize, Deserialize)]
+struct Posting {
+    id: u64,
+    /// Word positions of the term in the note.
+    positions: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    /// Hash of the indexed title and content, to spot stale entries.
+    hash: u64,
+    length: usize,
+    terms: Vec<String>,
+}
+
+/// Inverted index over note titles and contents, stored next to the
+/// notes so large stores do not have to be scanned on every query.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    documents: HashMap<u64, Document>,
+    postings: BTreeMap<String, Vec<Posting>>,
+    total_length: usize,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// A part of a query: a word, a `word*` prefix or a `"quoted phrase"`.
+pub enum QueryTerm {
+    Word(String),
+    Prefix(String),
+    Phrase(Vec<String>),
+}
+
+/// Lowercased words of a text, split on anything that is not alphanumeric.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+pub fn parse_query(query: &str) -> Vec<QueryTerm> {
+    let mut terms = Vec::new();
+    for (i, part) in query.split('"').enumerate() {
+        if i % 2 == 1 {
+            let words = tokenize(part);
+            match words.len() {
+                0 => {}
+                1 => terms.extend(words.into_iter().map(QueryTerm::Word)),
+                _ => terms.push(QueryTerm::Phrase(words)),
+            }
+            continue;
+        }
+        for word in part.split_whitespace() {
+            let prefix = word.ends_with('*');
+            for token in tokenize(word) {
+                terms.push(if prefix {
+                    QueryTerm::Prefix(token)
+                } else {
+                    QueryTerm::Word(token)
+                });
+            }
+        }
+    }
+    terms
+}
+
+/// FNV-1a hash of the text the index is built from. Unlike `modified`,
+/// it also changes when the title source changes the title.
+fn indexed_hash(note: &Note) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in note
+        .title
+        .bytes()
+        .chain([b'\n'])
+        .chain(note.content.bytes())
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub fn index_path() -> PathBuf {
+    data_dir().join("index.json")
+}
+
+impl SearchIndex {
+    pub fn build(notes: &[Note]) -> Self {
+        let mut index = SearchIndex {
+            dirty: true,
+            ..SearchIndex::default()
+        };
+        for note in notes {
+            index.update(note);
+        }
+        index
+    }
+
+    /// Loads the saved index and brings it up to date with `notes`.
+    pub fn load(notes: &[Note]) -> Self {
+        let mut index: SearchIndex = std::fs::read_to_string(index_path())
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        index.sync(notes);
+        index
+    }
+
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if let Ok(data) = serde_json::to_string(self) {
+            if std::fs::write(index_path(), data).is_ok() {
+                self.dirty = false;
+            }
+        }
+    }
+
+    /// Re-indexes notes changed since the index was saved and drops the
+    /// ones that no longer exist.
+    pub fn sync(&mut self, notes: &[Note]) {
+        let ids: HashSet<u64> = notes.iter().map(|note| note.id).collect();
+        let stale: Vec<u64> = self
+            .documents
+            .keys()
+            .copied()
+            .filter(|id| !ids.contains(id))
+            .collect();
+        for id in stale {
+            self.remove(id);
+        }
+        for note in notes {
+            let current = self
+                .documents
+                .get(&note.id)
+                .is_some_and(|doc| doc.hash == indexed_hash(note));
+            if !current {
+                self.update(note);
+            }
+        }
+    }
+
+    pub fn update(&mut self, note: &Note) {
+        self.remove(note.id);
+        let words = tokenize(&format!("{}\n{}", note.title, note.content));
+        let mut positions: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for (position, word) in words.iter().enumerate() {
+            positions
+                .entry(word.clone())
+                .or_default()
+                .push(position as u32);
+        }
+        let terms = positions.keys().cloned().collect();
+        for (term, positions) in positions {
+            self.postings.entry(term).or_default().push(Posting {
+                id: note.id,
+                positions,
+            });
+        }
+        self.total_length += words.len();
+        self.documents.insert(
+            note.id,
+            Document {
+                hash: indexed_hash(note),
+                length: words.len(),
+                terms,
+            },
+        );
+        self.dirty = true;
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        let Some(document) = self.documents.remove(&id) else {
+            return;
+        };
+        for term in document.terms {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.retain(|posting| posting.id != id);
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        self.total_length -= document.length;
+        self.dirty = true;
+    }
+
+    /// Term frequency per note for one query term.
+    fn frequencies(&self, term: &QueryTerm) -> HashMap<u64, usize> {
+        let mut frequencies = HashMap::new();
+        match term {
+            QueryTerm::Word(word) => {
+                for posting in self.postings.get(word).into_iter().flatten() {
+                    frequencies.insert(posting.id, posting.positions.len());
+                }
+            }
+            QueryTerm::Prefix(prefix) => {
+                let matching = self
+                    .postings
+                    .range(prefix.clone()..)
+                    .take_while(|(term, _)| term.starts_with(prefix.as_str()));
+                for (_, postings) in matching {
+                    for posting in postings {
+                        *frequencies.entry(posting.id).or_insert(0) += posting.positions.len();
+                    }
+                }
+            }
+            QueryTerm::Phrase(words) => {
+                let lists: Option<Vec<&Vec<Posting>>> =
+                    words.iter().map(|word| self.postings.get(word)).collect();
+                let Some(lists) = lists else {
+                    return frequencies;
+                };
+                let by_id: Vec<HashMap<u64, &Posting>> = lists[1..]
+                    .iter()
+                    .map(|list| list.iter().map(|posting| (posting.id, posting)).collect())
+                    .collect();
+                for first in lists[0] {
+                    let rest: Option<Vec<&Posting>> = by_id
+                        .iter()
+                        .map(|postings| postings.get(&first.id).copied())
+                        .collect();
+                    let Some(rest) = rest else {
+                        continue;
+                    };
+                    let count = first
+                        .positions
+                        .iter()
+                        .filter(|&&start| {
+                            rest.iter().enumerate().all(|(offset, posting)| {
+                                posting
+                                    .positions
+                                    .binary_search(&(start + offset as u32 + 1))
+                                    .is_ok()
+                            })
+                        })
+                        .count();
+                    if count > 0 {
+                        frequencies.insert(first.id, count);
+                    }
+                }
+            }
+        }
+        frequencies
+    }
+
+    /// Ids of the notes matching every term of the query, best BM25 score
+    /// first.
+    pub fn search(&self, query: &[QueryTerm]) -> Vec<(u64, f64)> {
+        if query.is_empty() || self.documents.is_empty() {
+            return Vec::new();
+        }
+        let count = self.documents.len() as f64;
+        let average_length = (self.total_length as f64 / count).max(1.0);
+
+        let mut scores: Option<HashMap<u64, f64>> = None;
+        for term in query {
+            let frequencies = self.frequencies(term);
+            let df = frequencies.len() as f64;
+            let idf = ((count - df + 0.5) / (df + 0.5) + 1.0).ln();
+            let mut term_scores = HashMap::new();
+            for (id, tf) in frequencies {
+                let length = self.documents.get(&id).map_or(0, |doc| doc.length) as f64;
+                let tf = tf as f64;
+                let score =
+                    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average_length));
+                term_scores.insert(id, score);
+            }
+            scores = Some(match scores {
+                None => term_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(id, score)| term_scores.get(&id).map(|s| (id, score + s)))
+                    .collect(),
+            });
+        }
+
+        let mut results: Vec<(u64, f64)> = scores.unwrap_or_default().into_iter().collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        results
+    }
+}
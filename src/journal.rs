@@ -0,0 +1,69 @@
+use crate::app::App;
+use crate::notes::{save_notes, Note};
+use crate::templates::{expand, load_templates};
+use crate::tree::normalize_folder;
+use chrono::{Local, NaiveDate};
+
+pub const JOURNAL_TEMPLATE: &str = "# {{date}} {{weekday}}\n\n";
+
+/// Content of a new daily note, from the `journal` template when there is one.
+fn journal_content() -> String {
+    let template = load_templates()
+        .into_iter()
+        .find(|template| template.name == "journal")
+        .map_or(JOURNAL_TEMPLATE.to_string(), |template| template.content);
+    expand(&template).0
+}
+
+/// Selects today's daily note, creating it in this month's journal folder
+/// when it does not exist yet.
+pub fn open_today(app: &mut App) {
+    let today = Local::now().date_naive();
+    let index = match journal_note(app, today) {
+        Some(index) => index,
+        None => {
+            let mut note = Note::new(journal_content(), app.config.title_source);
+            note.journal_date = Some(today);
+            note.folder = normalize_folder(&format!(
+                "{}/{}",
+                app.config.journal_folder,
+                today.format("%Y-%m")
+            ));
+            let index = app.add_note(note);
+            save_notes(&app.notes);
+            index
+        }
+    };
+    app.open_note(index);
+}
+
+/// The daily note of `date`, archived or not; `open_note` shows archived
+/// ones in the archive view.
+fn journal_note(app: &App, date: NaiveDate) -> Option<usize> {
+    app.notes
+        .iter()
+        .position(|note| note.journal_date == Some(date))
+}
+
+/// Selects the closest earlier or later daily note, counting from the
+/// selected daily note or from today.
+pub fn step_journal(app: &mut App, forward: bool) {
+    let from = app
+        .current_note()
+        .and_then(|index| app.notes[index].journal_date)
+        .unwrap_or_else(|| Local::now().date_naive());
+    let entries = app
+        .notes
+        .iter()
+        .enumerate()
+        .filter(|(_, note)| !note.archived)
+        .filter_map(|(index, note)| note.journal_date.map(|date| (date, index)));
+    let next = if forward {
+        entries.filter(|&(date, _)| date > from).min()
+    } else {
+        entries.filter(|&(date, _)| date < from).max()
+    };
+    if let Some((_, index)) = next {
+        app.open_note(index);
+    }
+}
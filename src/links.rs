@@ -0,0 +1,192 @@
+use crate::app::App;
+use crate::markdown::parse_markdown_to_lines;
+use crate::notes::{save_notes, Note};
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub struct WikiLink {
+    pub target: String,
+    pub label: String,
+    /// Byte range of the whole `[[...]]` token.
+    pub range: Range<usize>,
+}
+
+/// Finds `[[Target]]` and `[[Target|label]]` links in a line of text.
+pub fn find_wiki_links(text: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("[[").map(|i| i + offset) {
+        let inner_start = start + 2;
+        let Some(len) = text[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &text[inner_start..inner_start + len];
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target.trim(), label.trim()),
+            None => (inner.trim(), inner.trim()),
+        };
+        if !target.is_empty() && !inner.contains('[') {
+            links.push(WikiLink {
+                target: target.to_string(),
+                label: if label.is_empty() { target } else { label }.to_string(),
+                range: start..inner_start + len + 2,
+            });
+            offset = inner_start + len + 2;
+        } else {
+            offset = inner_start;
+        }
+    }
+    links
+}
+
+/// A link from one note to another, with the line it appears on.
+#[derive(Clone)]
+pub struct Backlink {
+    pub source: u64,
+    pub context: String,
+}
+
+/// Wiki-links of every note, kept up to date as notes change so backlinks
+/// can be looked up without rescanning all notes.
+#[derive(Default)]
+pub struct LinkIndex {
+    outgoing: HashMap<u64, Vec<String>>,
+    incoming: HashMap<String, Vec<Backlink>>,
+}
+
+impl LinkIndex {
+    pub fn build(notes: &[Note]) -> Self {
+        let mut index = LinkIndex::default();
+        for note in notes {
+            index.update(note);
+        }
+        index
+    }
+
+    /// Re-reads the links of a note after its content changed. Links are
+    /// taken from the rendered preview, so `[[...]]` in code is skipped.
+    pub fn update(&mut self, note: &Note) {
+        self.remove(note.id);
+        let mut targets = Vec::new();
+        for rendered in parse_markdown_to_lines(&note.content) {
+            for link in &rendered.links {
+                let target = link.to_lowercase();
+                let backlinks = self.incoming.entry(target.clone()).or_default();
+                if !backlinks.iter().any(|b| b.source == note.id) {
+                    backlinks.push(Backlink {
+                        source: note.id,
+                        context: rendered.line.to_string().trim().to_string(),
+                    });
+                }
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+        self.outgoing.insert(note.id, targets);
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        for target in self.outgoing.remove(&id).unwrap_or_default() {
+            if let Some(backlinks) = self.incoming.get_mut(&target) {
+                backlinks.retain(|b| b.source != id);
+                if backlinks.is_empty() {
+                    self.incoming.remove(&target);
+                }
+            }
+        }
+    }
+
+    /// Lowercased targets of the links in a note.
+    pub fn targets(&self, id: u64) -> &[String] {
+        self.outgoing.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Links pointing at a note by its title or one of its aliases.
+    pub fn backlinks(&self, note: &Note) -> Vec<Backlink> {
+        let mut backlinks: Vec<Backlink> = Vec::new();
+        let names = std::iter::once(&note.title).chain(&note.meta.aliases);
+        for name in names {
+            for backlink in self
+                .incoming
+                .get(&name.to_lowercase())
+                .into_iter()
+                .flatten()
+            {
+                if backlink.source != note.id
+                    && !backlinks.iter().any(|b| b.source == backlink.source)
+                {
+                    backlinks.push(backlink.clone());
+                }
+            }
+        }
+        backlinks
+    }
+}
+
+/// Finds the note a link points to by title or front matter alias.
+pub fn resolve_link(notes: &[Note], target: &str) -> Option<usize> {
+    let target = target.trim();
+    notes.iter().position(|note| {
+        note.title.eq_ignore_ascii_case(target)
+            || note
+                .meta
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(target))
+    })
+}
+
+/// Jumps to the first link on the selected preview line, creating the
+/// target note when it does not exist yet.
+pub fn follow_link(app: &mut App) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+    let lines = parse_markdown_to_lines(&app.notes[index].content);
+    let Some(target) = lines
+        .get(app.selected_line)
+        .and_then(|line| line.links.first())
+        .cloned()
+    else {
+        return;
+    };
+
+    let target_index = match resolve_link(&app.notes, &target) {
+        Some(target_index) => target_index,
+        None => {
+            let mut note = Note::new(format!("# {}\n", target), app.config.title_source);
+            note.folder = app.notes[index].folder.clone();
+            let target_index = app.add_note(note);
+            save_notes(&app.notes);
+            target_index
+        }
+    };
+
+    app.back_stack.push(app.notes[index].id);
+    app.open_note(target_index);
+}
+
+/// Jumps to the note the selected backlink comes from.
+pub fn open_backlink(app: &mut App) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+    let Some(backlink) = app.backlinks().get(app.selected_backlink).cloned() else {
+        return;
+    };
+    if let Some(source) = app.index_of(backlink.source) {
+        app.back_stack.push(app.notes[index].id);
+        app.open_note(source);
+    }
+}
+
+/// Returns to the note the last followed link was on.
+pub fn go_back(app: &mut App) {
+    while let Some(id) = app.back_stack.pop() {
+        if let Some(index) = app.index_of(id) {
+            app.open_note(index);
+            return;
+        }
+    }
+}
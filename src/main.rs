@@ -1,5 +1,23 @@
 mod app;
+mod calendar;
+mod config;
+mod editor;
+mod frontmatter;
+mod fuzzy;
+mod graph;
+mod index;
+mod journal;
+mod links;
+mod markdown;
 mod notes;
+mod query;
+mod replace;
+mod search;
+mod tags;
+mod tasks;
+mod templates;
+mod theme;
+mod tree;
 mod ui;
 mod utils;
 
@@ -22,7 +40,32 @@ use std::{
 use utils::handle_input;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::args().any(|arg| arg == "--reindex") {
+        let notes = notes::load_notes(config::load_config().title_source);
+        let mut index = index::SearchIndex::build(&notes);
+        index.save();
+        println!("Indexed {} notes", notes.len());
+        return Ok(());
+    }
+
+    let mut args = std::env::args().skip_while(|arg| arg != "--query");
+    if args.next().is_some() {
+        let notes = notes::load_notes(config::load_config().title_source);
+        let filter = query::parse_filter(&args.collect::<Vec<_>>().join(" "))?;
+        for index in search::filter_notes(&notes, &filter) {
+            let note = &notes[index];
+            match note.folder.as_str() {
+                "" => println!("{}", note.title),
+                folder => println!("{}/{}", folder, note.title),
+            }
+        }
+        return Ok(());
+    }
+
     let mut app = App::new();
+    if std::env::args().any(|arg| arg == "--today") {
+        journal::open_today(&mut app);
+    }
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -50,6 +93,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         if event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
                 should_quit = handle_input(key, &mut app)?;
+                // Only written when the key changed a note.
+                app.search_index.save();
             }
         }
 
@@ -0,0 +1,274 @@
+use crate::frontmatter::{parse_front_matter, FrontMatter};
+use crate::links::find_wiki_links;
+use crate::tasks::marker_text;
+use crate::theme::{MAROON, SAPPHIRE, SUBTEXT0, SUBTEXT1, TEAL};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+use std::ops::Range;
+
+pub struct RenderedLine {
+    pub line: Line<'static>,
+    /// Targets of the wiki-links on this line.
+    pub links: Vec<String>,
+    /// Bytes of the note content the line was rendered from.
+    pub source: Range<usize>,
+    /// Lines of the note content the line was rendered from, counted from 0.
+    pub lines: Range<usize>,
+}
+
+/// Text of the preview line being built, with where it came from.
+#[derive(Default)]
+struct PendingLine {
+    text: String,
+    source: Option<Range<usize>>,
+    /// Parts of `text` that are code, where `[[...]]` is not a link.
+    code: Vec<Range<usize>>,
+}
+
+impl PendingLine {
+    fn push_str(&mut self, text: &str, range: Range<usize>) {
+        self.text.push_str(text);
+        extend_source(&mut self.source, range);
+    }
+
+    fn push_code(&mut self, text: &str, range: Range<usize>) {
+        let start = self.text.len();
+        self.push_str(text, range);
+        self.code.push(start..self.text.len());
+    }
+
+    /// Pushes the line unless it is empty, and starts a new one.
+    fn flush(&mut self, lines: &mut Vec<RenderedLine>, style: Style, input: &str) {
+        let pending = std::mem::take(self);
+        if !pending.text.is_empty() {
+            push_line(
+                lines,
+                &pending.text,
+                style,
+                input,
+                pending.source,
+                &pending.code,
+            );
+        }
+    }
+}
+
+pub fn parse_markdown_to_lines(input: &str) -> Vec<RenderedLine> {
+    let parser = Parser::new_ext(input, Options::all()).into_offset_iter();
+    let mut lines = Vec::new();
+    let mut current = PendingLine::default();
+    let mut in_heading = false;
+    let mut in_metadata = false;
+    let mut in_code_block = false;
+    let code_style = Style::default().fg(SUBTEXT1);
+    let heading_style = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+    for (event, range) in parser {
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::MetadataBlock(_) => {
+                    in_metadata = true;
+                    if let Some(front_matter) = parse_front_matter(input) {
+                        if let Some(header) = front_matter_header(&front_matter) {
+                            lines.push(RenderedLine {
+                                line: header,
+                                links: Vec::new(),
+                                lines: source_lines(input, &range),
+                                source: range,
+                            });
+                        }
+                    }
+                }
+                Tag::Heading { .. } => {
+                    in_heading = true;
+                    current.flush(&mut lines, Style::default(), input);
+                    current.source = Some(range);
+                }
+                Tag::Item => {
+                    current.flush(&mut lines, Style::default(), input);
+                    // The item starts at its bullet; its text extends it.
+                    current.push_str("• ", range.start..range.start);
+                }
+                Tag::CodeBlock(_) => {
+                    in_code_block = true;
+                    current.flush(&mut lines, Style::default(), input);
+                }
+                _ => {}
+            },
+            Event::End(tagend) => match tagend {
+                TagEnd::MetadataBlock(_) => {
+                    in_metadata = false;
+                }
+                TagEnd::Heading { .. } => {
+                    current.flush(&mut lines, heading_style, input);
+                    in_heading = false;
+                }
+                TagEnd::Paragraph => current.flush(&mut lines, Style::default(), input),
+                TagEnd::CodeBlock => {
+                    current.flush(&mut lines, code_style, input);
+                    in_code_block = false;
+                }
+                _ => {}
+            },
+            Event::Text(t) if in_code_block => {
+                // Code keeps its own lines, each mapped to its source line.
+                let mut offset = range.start;
+                for piece in t.split_inclusive('\n') {
+                    let text = piece.trim_end_matches('\n');
+                    current.push_code(text, offset..offset + text.len());
+                    if piece.ends_with('\n') {
+                        current.flush(&mut lines, code_style, input);
+                    }
+                    offset += piece.len();
+                }
+            }
+            Event::Code(t) => current.push_code(&t, range),
+            Event::Text(t) if !in_metadata => current.push_str(&t, range),
+            Event::SoftBreak | Event::HardBreak => {
+                if !current.text.is_empty() {
+                    let style = if in_heading {
+                        heading_style
+                    } else {
+                        Style::default()
+                    };
+                    current.flush(&mut lines, style, input);
+                } else {
+                    push_line(&mut lines, "", Style::default(), input, Some(range), &[]);
+                }
+            }
+            Event::TaskListMarker(checked) => {
+                current.push_str(&format!("{} ", marker_text(checked)), range);
+            }
+            _ => {}
+        }
+    }
+
+    let style = if in_heading {
+        heading_style
+    } else {
+        Style::default()
+    };
+    current.flush(&mut lines, style, input);
+
+    lines
+}
+
+fn extend_source(source: &mut Option<Range<usize>>, range: Range<usize>) {
+    *source = Some(match source.take() {
+        Some(source) => source.start.min(range.start)..source.end.max(range.end),
+        None => range,
+    });
+}
+
+/// Content lines a byte range of `input` spans.
+fn source_lines(input: &str, range: &Range<usize>) -> Range<usize> {
+    let line_at = |offset: usize| input[..offset.min(input.len())].matches('\n').count();
+    let start = line_at(range.start);
+    let end = line_at(range.end.saturating_sub(1).max(range.start));
+    start..end + 1
+}
+
+/// Index of the rendered line showing content line `line`, or of the next
+/// one when the line is not shown, like a blank line.
+pub fn rendered_line_at(lines: &[RenderedLine], line: usize) -> usize {
+    lines
+        .iter()
+        .position(|rendered| rendered.lines.end > line)
+        .unwrap_or(lines.len().saturating_sub(1))
+}
+
+/// Pushes a rendered line, styling its wiki-links outside `code` as links.
+fn push_line(
+    lines: &mut Vec<RenderedLine>,
+    text: &str,
+    style: Style,
+    input: &str,
+    source: Option<Range<usize>>,
+    code: &[Range<usize>],
+) {
+    let mut spans = Vec::new();
+    let mut links = Vec::new();
+    let mut last = 0;
+    let in_code = |link: &Range<usize>| {
+        code.iter()
+            .any(|code| code.start < link.end && link.start < code.end)
+    };
+    for link in find_wiki_links(text) {
+        if in_code(&link.range) {
+            continue;
+        }
+        if link.range.start > last {
+            spans.push(Span::styled(
+                text[last..link.range.start].to_string(),
+                style,
+            ));
+        }
+        spans.push(Span::styled(
+            link.label,
+            style.fg(SAPPHIRE).add_modifier(Modifier::UNDERLINED),
+        ));
+        links.push(link.target);
+        last = link.range.end;
+    }
+    if last < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[last..].to_string(), style));
+    }
+    // Lines without a source of their own follow the previous line.
+    let source = source.unwrap_or_else(|| {
+        let end = lines.last().map_or(0, |line| line.source.end);
+        end..end
+    });
+    lines.push(RenderedLine {
+        line: Line::from(spans),
+        links,
+        lines: source_lines(input, &source),
+        source,
+    });
+}
+
+/// Renders the front matter as a single compact line instead of the raw block.
+fn front_matter_header(front_matter: &FrontMatter) -> Option<Line<'static>> {
+    let meta_style = Style::default().fg(SUBTEXT0).add_modifier(Modifier::ITALIC);
+    let mut parts = Vec::new();
+
+    if front_matter.pinned {
+        parts.push(Span::styled("pinned", meta_style));
+    }
+    if !front_matter.tags.is_empty() {
+        let tags = front_matter
+            .tags
+            .iter()
+            .map(|t| format!("#{}", t))
+            .collect::<Vec<_>>()
+            .join(" ");
+        parts.push(Span::styled(tags, Style::default().fg(TEAL)));
+    }
+    if !front_matter.aliases.is_empty() {
+        parts.push(Span::styled(
+            format!("aka {}", front_matter.aliases.join(", ")),
+            meta_style,
+        ));
+    }
+    if let Some(due) = front_matter.due {
+        parts.push(Span::styled(
+            format!("due {}", due.format("%Y-%m-%d")),
+            Style::default().fg(MAROON),
+        ));
+    }
+
+    if parts.is_empty() {
+        return None;
+    }
+
+    let mut spans = Vec::new();
+    for (i, part) in parts.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" · ", meta_style));
+        }
+        spans.push(part);
+    }
+    Some(Line::from(spans))
+}
@@ -1,21 +1,135 @@
-use crate::app::App;
+use crate::app::{App, CaptureTarget, Focus, Mode};
+use crate::config::{save_config, SortMode, TitleSource};
+use crate::editor::Editor;
+use crate::frontmatter::{parse_front_matter, split_front_matter, FrontMatter};
+use crate::markdown::{parse_markdown_to_lines, rendered_line_at};
+use crate::tags::extract_tags;
+use crate::tasks::{count_todos, toggle_task};
+use crate::templates::{expand, Template};
+use crate::tree::normalize_folder;
 use crate::utils::notes_path;
+use chrono::{DateTime, NaiveDate, Utc};
 use crossterm::event::EnableMouseCapture;
 use crossterm::terminal::EnterAlternateScreen;
 use crossterm::{execute, terminal::enable_raw_mode};
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::io;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Note {
+    #[serde(default)]
+    pub id: u64,
     pub title: String,
     pub content: String,
+    #[serde(default)]
+    pub explicit_title: Option<String>,
+    #[serde(default)]
+    pub folder: String,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub archived: bool,
+    /// Unix timestamps in seconds.
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default)]
+    pub modified: i64,
+    /// Day a daily note belongs to.
+    #[serde(default)]
+    pub journal_date: Option<NaiveDate>,
+    #[serde(skip)]
+    pub meta: FrontMatter,
+    #[serde(skip)]
+    pub tags: Vec<String>,
+    /// Open and done tasks.
+    #[serde(skip)]
+    pub todos: (usize, usize),
 }
 
-pub fn load_notes() -> Vec<Note> {
+impl Note {
+    pub fn new(content: String, source: TitleSource) -> Self {
+        let now = Utc::now().timestamp();
+        let mut note = Note {
+            id: 0,
+            title: String::new(),
+            content: String::new(),
+            explicit_title: None,
+            folder: String::new(),
+            pinned: false,
+            archived: false,
+            created: now,
+            modified: now,
+            journal_date: None,
+            meta: FrontMatter::default(),
+            tags: Vec::new(),
+            todos: (0, 0),
+        };
+        note.set_content(content, source);
+        note
+    }
+
+    pub fn set_content(&mut self, content: String, source: TitleSource) {
+        self.modified = Utc::now().timestamp();
+        self.content = content;
+        self.refresh_metadata();
+        self.update_title(source);
+    }
+
+    /// Recomputes the fields derived from the content.
+    pub fn refresh_metadata(&mut self) {
+        self.meta = parse_front_matter(&self.content).unwrap_or_default();
+        self.tags = extract_tags(&self.content);
+        self.todos = count_todos(&self.content);
+    }
+
+    /// Pinned either through the pin key or the front matter.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned || self.meta.pinned
+    }
+
+    pub fn update_title(&mut self, source: TitleSource) {
+        let derived = title_from_content(&self.content);
+        self.title = match (&self.explicit_title, source) {
+            (Some(explicit), TitleSource::Explicit) => explicit.clone(),
+            (Some(explicit), TitleSource::Derived) if derived == "Untitled" => explicit.clone(),
+            _ => derived,
+        };
+    }
+}
+
+pub fn load_notes(source: TitleSource) -> Vec<Note> {
     let notes_file = notes_path();
     if let Ok(data) = std::fs::read_to_string(&notes_file) {
-        if let Ok(notes) = serde_json::from_str(&data) {
+        if let Ok(mut notes) = serde_json::from_str::<Vec<Note>>(&data) {
+            let mut next_id = notes.iter().map(|n| n.id).max().unwrap_or(0) + 1;
+            // Notes saved before timestamps existed get the time the notes
+            // file was last written.
+            let saved_at = std::fs::metadata(&notes_file)
+                .and_then(|meta| meta.modified())
+                .map(|time| DateTime::<Utc>::from(time).timestamp())
+                .unwrap_or_else(|_| Utc::now().timestamp());
+            let mut backfilled = false;
+            for note in notes.iter_mut() {
+                if note.id == 0 {
+                    note.id = next_id;
+                    next_id += 1;
+                }
+                if note.created == 0 || note.modified == 0 {
+                    if note.created == 0 {
+                        note.created = saved_at;
+                    }
+                    if note.modified == 0 {
+                        note.modified = saved_at;
+                    }
+                    backfilled = true;
+                }
+                note.refresh_metadata();
+                note.update_title(source);
+            }
+            if backfilled {
+                save_notes(&notes);
+            }
             return notes;
         }
     }
@@ -29,25 +143,20 @@ pub fn save_notes(notes: &[Note]) {
     }
 }
 
-pub fn create_note(app: &mut App) -> io::Result<()> {
+pub fn create_note(app: &mut App, template: &Template) -> io::Result<()> {
     let tmpfile = "tmp_new_note.md";
-    std::fs::write(tmpfile, "# Title\n\n- [ ] New item")?;
-    std::process::Command::new("nvim").arg(tmpfile).status()?;
+    let (initial, cursor) = expand(&template.content);
+    std::fs::write(tmpfile, initial)?;
+    let mut command = std::process::Command::new("nvim");
+    if let Some((line, col)) = cursor {
+        command.arg(format!("+call cursor({}, {})", line, col));
+    }
+    command.arg(tmpfile).status()?;
 
     let content = std::fs::read_to_string(tmpfile)?;
-    let title_line = content
-        .lines()
-        .next()
-        .unwrap_or("Untitled")
-        .trim()
-        .trim_start_matches('#')
-        .trim();
-    let title = if title_line.is_empty() {
-        "Untitled".to_string()
-    } else {
-        title_line.to_string()
-    };
-    app.notes.push(Note { title, content });
+    let mut note = Note::new(content, app.config.title_source);
+    note.folder = app.current_folder();
+    app.add_note(note);
     std::fs::remove_file(tmpfile)?;
 
     save_notes(&app.notes);
@@ -63,28 +172,21 @@ pub fn create_note(app: &mut App) -> io::Result<()> {
 }
 
 pub fn edit_note(app: &mut App) -> io::Result<()> {
-    if app.notes.is_empty() {
+    let Some(index) = app.current_note() else {
         return Ok(());
-    }
+    };
     let tmpfile = "tmp_edit_note.md";
-    let curr = &app.notes[app.selected_note];
+    let curr = &app.notes[index];
     std::fs::write(tmpfile, &curr.content)?;
-    std::process::Command::new("nvim").arg(tmpfile).status()?;
+    let line = selected_source_line(app, index).unwrap_or(0);
+    std::process::Command::new("nvim")
+        .arg(format!("+{}", line + 1))
+        .arg(tmpfile)
+        .status()?;
 
     let content = std::fs::read_to_string(tmpfile)?;
-    let title_line = content
-        .lines()
-        .next()
-        .unwrap_or("Untitled")
-        .trim()
-        .trim_start_matches('#')
-        .trim();
-    let title = if title_line.is_empty() {
-        "Untitled".to_string()
-    } else {
-        title_line.to_string()
-    };
-    app.notes[app.selected_note] = Note { title, content };
+    app.notes[index].set_content(content, app.config.title_source);
+    app.note_changed(index);
     std::fs::remove_file(tmpfile)?;
 
     save_notes(&app.notes);
@@ -98,18 +200,204 @@ pub fn edit_note(app: &mut App) -> io::Result<()> {
     Ok(())
 }
 
-pub fn delete_note(app: &mut App) {
-    if app.notes.is_empty() {
+/// Content line the selected preview line was rendered from.
+pub fn selected_source_line(app: &App, index: usize) -> Option<usize> {
+    parse_markdown_to_lines(&app.notes[index].content)
+        .get(app.selected_line)
+        .map(|rendered| rendered.lines.start)
+}
+
+pub fn start_editing(app: &mut App) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+    let mut editor = Editor::new(&app.notes[index].content);
+    if let Some(line) = selected_source_line(app, index) {
+        editor.row = line.min(editor.lines.len() - 1);
+    }
+    app.editor = Some(editor);
+    app.mode = Mode::Insert;
+    app.focus = Focus::Right;
+}
+
+pub fn finish_editing(app: &mut App) {
+    if let Some(editor) = app.editor.take() {
+        if let Some(index) = app.current_note() {
+            let content = editor.content();
+            if content != app.notes[index].content {
+                app.notes[index].set_content(content, app.config.title_source);
+                app.note_changed(index);
+                save_notes(&app.notes);
+            }
+            let lines = parse_markdown_to_lines(&app.notes[index].content);
+            app.selected_line = rendered_line_at(&lines, editor.row);
+        }
+    }
+    app.mode = Mode::Normal;
+}
+
+/// Adds the captured text to the chosen target. Returns false when it
+/// could not be captured, to keep the prompt open.
+pub fn capture(app: &mut App) -> bool {
+    let text = app.input.value.trim().to_string();
+    if text.is_empty() {
+        return true;
+    }
+
+    match app.capture_target {
+        CaptureTarget::NewNote => {
+            let content = format!("# {}\n", text);
+            let mut note = Note::new(content, app.config.title_source);
+            note.folder = app.current_folder();
+            app.add_note(note);
+        }
+        CaptureTarget::SelectedNote => {
+            let Some(index) = app.current_note() else {
+                app.status = Some("No note is selected".to_string());
+                return false;
+            };
+            append_todo(app, index, &text);
+        }
+        CaptureTarget::Inbox => {
+            let inbox = &app.config.inbox_title;
+            match app
+                .notes
+                .iter()
+                .position(|n| !n.archived && n.title.eq_ignore_ascii_case(inbox))
+            {
+                Some(i) => append_todo(app, i, &text),
+                None => {
+                    let content = format!("# {}\n\n- [ ] {}", inbox, text);
+                    app.add_note(Note::new(content, app.config.title_source));
+                }
+            }
+        }
+    }
+
+    save_notes(&app.notes);
+    true
+}
+
+fn append_todo(app: &mut App, index: usize, text: &str) {
+    let mut content = app.notes[index].content.trim_end().to_string();
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str("- [ ] ");
+    content.push_str(text);
+    app.notes[index].set_content(content, app.config.title_source);
+    app.note_changed(index);
+}
+
+/// Applies the rename prompt; an empty title reverts to the derived one.
+pub fn rename_note(app: &mut App) {
+    let Some(index) = app.current_note() else {
         return;
+    };
+    let title = app.input.value.trim();
+    let note = &mut app.notes[index];
+    note.explicit_title = if title.is_empty() {
+        None
+    } else {
+        Some(title.to_string())
+    };
+    note.update_title(app.config.title_source);
+    note.modified = Utc::now().timestamp();
+    app.note_changed(index);
+    save_notes(&app.notes);
+}
+
+/// Returns whether a note other than the selected one is titled `title`.
+pub fn is_duplicate_title(app: &App, title: &str) -> bool {
+    let title = title.trim();
+    app.notes
+        .iter()
+        .any(|n| n.id != app.selected_id && n.title.eq_ignore_ascii_case(title))
+}
+
+/// Moves the selected note into the folder typed in the prompt and expands
+/// the folder so the note stays in view.
+pub fn move_note(app: &mut App) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+    app.notes[index].folder = normalize_folder(&app.input.value);
+    app.reveal_note(index);
+    app.count_saved_searches();
+    save_notes(&app.notes);
+}
+
+/// Moves the selected note one place up or down among its siblings in the
+/// manual order, switching to manual sorting if another order is active.
+pub fn reorder_note(app: &mut App, down: bool) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+    if app.config.sort_mode != SortMode::Manual {
+        app.config.sort_mode = SortMode::Manual;
+        save_config(&app.config);
     }
 
-    app.notes.remove(app.selected_note);
-    if app.selected_note >= app.notes.len() && !app.notes.is_empty() {
-        app.selected_note = app.notes.len() - 1;
+    let note = &app.notes[index];
+    let siblings: Vec<usize> = app
+        .visible_notes()
+        .into_iter()
+        .filter(|&i| {
+            app.notes[i].folder == note.folder && app.notes[i].is_pinned() == note.is_pinned()
+        })
+        .collect();
+    let Some(position) = siblings.iter().position(|&i| i == index) else {
+        return;
+    };
+    let target = if down {
+        siblings.get(position + 1)
+    } else {
+        position.checked_sub(1).and_then(|p| siblings.get(p))
+    };
+
+    if let Some(&target) = target {
+        let note = app.notes.remove(index);
+        app.notes.insert(target, note);
+        save_notes(&app.notes);
     }
+}
+
+pub fn toggle_pin(app: &mut App) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+    let note = &mut app.notes[index];
+    if note.meta.pinned {
+        app.status = Some("Pinned in the front matter; edit the note to unpin it".to_string());
+        return;
+    }
+    note.pinned = !note.pinned;
+    app.count_saved_searches();
+    save_notes(&app.notes);
+}
+
+/// Archives the selected note, or restores it when viewing the archive.
+pub fn toggle_archive(app: &mut App) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+    app.select_neighbour(index);
+    let note = &mut app.notes[index];
+    note.archived = !note.archived;
+    app.count_saved_searches();
+    save_notes(&app.notes);
+}
+
+pub fn delete_note(app: &mut App) {
+    let Some(index) = app.current_note() else {
+        return;
+    };
+
+    app.select_neighbour(index);
+    app.remove_note(index);
 
     if app.notes.is_empty() {
-        app.selected_note = 0;
+        app.selected_id = 0;
         app.list_scroll = 0;
     }
 
@@ -117,38 +405,66 @@ pub fn delete_note(app: &mut App) {
 }
 
 pub fn toggle_todo(app: &mut App) {
-    if app.notes.is_empty() {
+    let Some(index) = app.current_note() else {
         return;
-    }
-    let mut lines = app.notes[app.selected_note]
-        .content
-        .lines()
-        .map(|l| l.to_string())
-        .collect::<Vec<_>>();
-
-    let curr_line = app.selected_line + 1;
+    };
+    let content = &app.notes[index].content;
+    let Some(source) = parse_markdown_to_lines(content)
+        .get(app.selected_line)
+        .map(|rendered| rendered.source.clone())
+    else {
+        return;
+    };
 
-    if curr_line < lines.len() {
-        let line = &mut lines[curr_line];
-        if line.contains("[ ]") {
-            *line = line.replacen("[ ]", "[x]", 1);
-        } else if line.contains("[x]") {
-            *line = line.replacen("[x]", "[ ]", 1);
-        }
-        app.notes[app.selected_note].content = lines.join("\n");
+    if let Some(content) = toggle_task(content, &source) {
+        app.notes[index].set_content(content, app.config.title_source);
+        app.note_changed(index);
         crate::notes::save_notes(&app.notes);
     }
 }
 
-pub fn count_todos(content: &str) -> (usize, usize) {
-    let mut open = 0;
-    let mut closed = 0;
-    for line in content.lines() {
-        if line.contains("[ ]") {
-            open += 1;
-        } else if line.contains("[x]") {
-            closed += 1;
+/// Orders note indexes by `mode`. The sort is stable, so notes that compare
+/// equal keep their manual order.
+pub fn sort_notes(notes: &[Note], indexes: &mut [usize], mode: SortMode) {
+    match mode {
+        SortMode::Manual => {}
+        SortMode::Title => {
+            indexes.sort_by_cached_key(|&i| notes[i].title.to_lowercase());
         }
+        SortMode::Modified => indexes.sort_by_key(|&i| Reverse(notes[i].modified)),
+        SortMode::Created => indexes.sort_by_key(|&i| Reverse(notes[i].created)),
+        SortMode::OpenTodos => {
+            indexes.sort_by_key(|&i| Reverse(notes[i].todos.0));
+        }
+        SortMode::Completion => {
+            // Least complete first; notes without todos go last.
+            indexes.sort_by_key(|&i| {
+                let (open, closed) = notes[i].todos;
+                match (closed * 1000).checked_div(open + closed) {
+                    Some(ratio) => (0, ratio),
+                    None => (1, 0),
+                }
+            });
+        }
+    }
+}
+
+pub fn title_from_content(content: &str) -> String {
+    let (front_matter, body) = split_front_matter(content);
+    if let Some(title) = front_matter.and_then(|fm| fm.title) {
+        return title;
+    }
+    let title_line = body
+        .trim_start_matches(['\r', '\n'])
+        .lines()
+        .next()
+        .unwrap_or("Untitled")
+        .trim()
+        .trim_start_matches('#')
+        .trim();
+    if title_line.is_empty() {
+        "Untitled".to_string()
+    } else {
+        title_line.to_string()
     }
-    (open, closed)
 }
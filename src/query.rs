@@ -0,0 +1,363 @@
+use crate::notes::Note;
+use crate::tree::is_in_folder;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Before,
+    After,
+    On,
+}
+
+/// A point in time a date predicate compares against.
+#[derive(Debug, PartialEq)]
+pub enum When {
+    /// An age like `7d`, counted back from now.
+    Ago(i64),
+    Date(NaiveDate),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Filter {
+    All(Vec<Filter>),
+    Not(Box<Filter>),
+    /// Case-insensitive text in the title or content.
+    Text(String),
+    Title(String),
+    Tag(String),
+    Folder(String),
+    OpenTodos,
+    Done,
+    Pinned,
+    Archived,
+    Journal,
+    Overdue,
+    Updated(Comparison, When),
+    Created(Comparison, When),
+    Due(Comparison, NaiveDate),
+}
+
+/// Splits a query into words, keeping `"quoted text"` together. Quoted
+/// words are returned with their quotes.
+fn split_words(query: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn parse_flag(flag: &str) -> Option<Filter> {
+    Some(match flag {
+        "open-todos" | "open" | "todo" => Filter::OpenTodos,
+        "done" => Filter::Done,
+        "pinned" => Filter::Pinned,
+        "archived" => Filter::Archived,
+        "journal" | "daily" => Filter::Journal,
+        "overdue" => Filter::Overdue,
+        _ => return None,
+    })
+}
+
+/// Parses `<7d`, `>2024-01-31` or `2024-01-31`. Ages take the units `h`,
+/// `d`, `w`, `mo` (30 days) and `y` (365 days).
+fn parse_comparison(value: &str) -> Result<(Comparison, When), String> {
+    let (comparison, value) = match value.chars().next() {
+        Some('<') => (Comparison::Before, &value[1..]),
+        Some('>') => (Comparison::After, &value[1..]),
+        _ => (Comparison::On, value),
+    };
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok((comparison, When::Date(date)));
+    }
+    if value.is_empty() {
+        return Err("missing date or age".to_string());
+    }
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(digits);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid date or age '{}'", value))?;
+    let seconds = match unit {
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        "mo" => 30 * 86_400,
+        "y" => 365 * 86_400,
+        "" => return Err(format!("missing unit in '{}'", value)),
+        "m" => {
+            return Err(format!(
+                "ambiguous unit 'm' in '{}', use 'mo' for months",
+                value
+            ))
+        }
+        _ => return Err(format!("unknown unit '{}' in '{}'", unit, value)),
+    };
+    // An age of less than 7 days means after the point 7 days ago.
+    let comparison = match comparison {
+        Comparison::Before => Comparison::After,
+        Comparison::After => Comparison::Before,
+        Comparison::On => Comparison::On,
+    };
+    Ok((comparison, When::Ago(amount * seconds)))
+}
+
+fn parse_term(word: &str) -> Result<Filter, String> {
+    if let Some(negated) = word.strip_prefix('-').filter(|rest| !rest.is_empty()) {
+        return Ok(Filter::Not(Box::new(parse_term(negated)?)));
+    }
+    if word.starts_with('"') {
+        return Ok(Filter::Text(word.trim_matches('"').to_lowercase()));
+    }
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(parse_flag(word).unwrap_or_else(|| Filter::Text(word.to_lowercase())));
+    };
+    let value = value.trim_matches('"');
+    match key {
+        "tag" => Ok(Filter::Tag(value.trim_start_matches('#').to_lowercase())),
+        "is" => parse_flag(value).ok_or_else(|| format!("unknown flag 'is:{}'", value)),
+        "title" => Ok(Filter::Title(value.to_lowercase())),
+        "folder" | "in" => Ok(Filter::Folder(value.trim_matches('/').to_string())),
+        "updated" | "modified" => {
+            parse_comparison(value).map(|(comparison, when)| Filter::Updated(comparison, when))
+        }
+        "created" => {
+            parse_comparison(value).map(|(comparison, when)| Filter::Created(comparison, when))
+        }
+        "due" => match parse_comparison(value)? {
+            (comparison, When::Date(date)) => Ok(Filter::Due(comparison, date)),
+            (_, When::Ago(_)) => Err("due: takes a date like due:<2024-01-31".to_string()),
+        },
+        _ => Err(format!("unknown field '{}:'", key)),
+    }
+}
+
+/// Parses a query; every term has to match.
+pub fn parse_filter(query: &str) -> Result<Filter, String> {
+    split_words(query)
+        .iter()
+        .map(|word| parse_term(word))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Filter::All)
+}
+
+fn local_date(timestamp: i64) -> NaiveDate {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.with_timezone(&Local).date_naive())
+        .unwrap_or_default()
+}
+
+fn compare_timestamp(timestamp: i64, comparison: Comparison, when: &When, now: i64) -> bool {
+    match when {
+        When::Ago(seconds) => {
+            let point = now - seconds;
+            match comparison {
+                Comparison::Before => timestamp < point,
+                Comparison::After => timestamp > point,
+                Comparison::On => local_date(timestamp) == local_date(point),
+            }
+        }
+        When::Date(date) => compare_date(local_date(timestamp), comparison, *date),
+    }
+}
+
+fn compare_date(date: NaiveDate, comparison: Comparison, other: NaiveDate) -> bool {
+    match comparison {
+        Comparison::Before => date < other,
+        Comparison::After => date > other,
+        Comparison::On => date == other,
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, note: &Note) -> bool {
+        self.matches_at(note, Utc::now().timestamp())
+    }
+
+    fn matches_at(&self, note: &Note, now: i64) -> bool {
+        match self {
+            Filter::All(filters) => filters.iter().all(|f| f.matches_at(note, now)),
+            Filter::Not(filter) => !filter.matches_at(note, now),
+            Filter::Text(text) => {
+                note.title.to_lowercase().contains(text)
+                    || note.content.to_lowercase().contains(text)
+            }
+            Filter::Title(text) => note.title.to_lowercase().contains(text),
+            Filter::Tag(tag) => note.tags.contains(tag),
+            Filter::Folder(folder) => is_in_folder(&note.folder, folder),
+            Filter::OpenTodos => note.todos.0 > 0,
+            Filter::Done => {
+                let (open, closed) = note.todos;
+                open == 0 && closed > 0
+            }
+            Filter::Pinned => note.is_pinned(),
+            Filter::Archived => note.archived,
+            Filter::Journal => note.journal_date.is_some(),
+            Filter::Overdue => {
+                let today = Local.timestamp_opt(now, 0).single().map(|d| d.date_naive());
+                note.meta.due.is_some_and(|due| Some(due) < today)
+                    && !Filter::Done.matches_at(note, now)
+            }
+            Filter::Updated(comparison, when) => {
+                compare_timestamp(note.modified, *comparison, when, now)
+            }
+            Filter::Created(comparison, when) => {
+                compare_timestamp(note.created, *comparison, when, now)
+            }
+            Filter::Due(comparison, date) => note
+                .meta
+                .due
+                .is_some_and(|due| compare_date(due, *comparison, *date)),
+        }
+    }
+
+    /// Texts the query looks for, to highlight them in results.
+    pub fn texts(&self) -> Vec<&str> {
+        match self {
+            Filter::All(filters) => filters.iter().flat_map(Filter::texts).collect(),
+            Filter::Text(text) | Filter::Title(text) => vec![text.as_str()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_ages() {
+        assert_eq!(
+            parse_comparison("<7d"),
+            Ok((Comparison::After, When::Ago(7 * DAY)))
+        );
+        assert_eq!(
+            parse_comparison(">2w"),
+            Ok((Comparison::Before, When::Ago(14 * DAY)))
+        );
+        assert_eq!(
+            parse_comparison("12h"),
+            Ok((Comparison::On, When::Ago(12 * 3600)))
+        );
+        assert_eq!(
+            parse_comparison("<3mo"),
+            Ok((Comparison::After, When::Ago(90 * DAY)))
+        );
+        assert_eq!(
+            parse_comparison(">1y"),
+            Ok((Comparison::Before, When::Ago(365 * DAY)))
+        );
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(
+            parse_comparison("<2024-01-31"),
+            Ok((Comparison::Before, When::Date(date("2024-01-31"))))
+        );
+        assert_eq!(
+            parse_comparison(">2024-01-31"),
+            Ok((Comparison::After, When::Date(date("2024-01-31"))))
+        );
+        assert_eq!(
+            parse_comparison("2024-01-31"),
+            Ok((Comparison::On, When::Date(date("2024-01-31"))))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_comparisons() {
+        assert!(parse_comparison("").is_err());
+        assert!(parse_comparison("<").is_err());
+        assert!(parse_comparison("7").is_err());
+        assert!(parse_comparison("d").is_err());
+        assert!(parse_comparison("7x").is_err());
+        assert!(parse_comparison("2024-13-01").is_err());
+        assert!(parse_comparison("3m").unwrap_err().contains("'mo'"));
+    }
+
+    #[test]
+    fn parses_terms() {
+        assert_eq!(
+            parse_filter("tag:#Work is:open-todos pinned Deploy"),
+            Ok(Filter::All(vec![
+                Filter::Tag("work".to_string()),
+                Filter::OpenTodos,
+                Filter::Pinned,
+                Filter::Text("deploy".to_string()),
+            ]))
+        );
+        assert_eq!(
+            parse_filter("title:Plan folder:/work/projects/ in:home"),
+            Ok(Filter::All(vec![
+                Filter::Title("plan".to_string()),
+                Filter::Folder("work/projects".to_string()),
+                Filter::Folder("home".to_string()),
+            ]))
+        );
+        assert_eq!(parse_filter(""), Ok(Filter::All(Vec::new())));
+    }
+
+    #[test]
+    fn parses_quotes_and_negation() {
+        assert_eq!(
+            parse_filter(r#""release notes" -archived -"draft" title:"Big plan""#),
+            Ok(Filter::All(vec![
+                Filter::Text("release notes".to_string()),
+                Filter::Not(Box::new(Filter::Archived)),
+                Filter::Not(Box::new(Filter::Text("draft".to_string()))),
+                Filter::Title("big plan".to_string()),
+            ]))
+        );
+        // A lone dash is a word, not a negation.
+        assert_eq!(
+            parse_filter("-"),
+            Ok(Filter::All(vec![Filter::Text("-".to_string())]))
+        );
+    }
+
+    #[test]
+    fn parses_date_fields() {
+        assert_eq!(
+            parse_filter("updated:<7d created:>2024-01-01 due:<2024-02-01"),
+            Ok(Filter::All(vec![
+                Filter::Updated(Comparison::After, When::Ago(7 * DAY)),
+                Filter::Created(Comparison::After, When::Date(date("2024-01-01"))),
+                Filter::Due(Comparison::Before, date("2024-02-01")),
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_terms() {
+        assert!(parse_filter("is:nothing").is_err());
+        assert!(parse_filter("color:red").is_err());
+        assert!(parse_filter("due:<7d").is_err());
+        assert!(parse_filter("updated:soon").is_err());
+        assert!(parse_filter("tag:work updated:2m").is_err());
+    }
+}
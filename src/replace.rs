@@ -0,0 +1,115 @@
+use crate::app::App;
+use crate::notes::{save_notes, Note};
+use crate::search::regex_error_message;
+use regex::{NoExpand, Regex};
+
+/// A line that changes when the replacement is applied.
+pub struct ReplaceHit {
+    pub index: usize,
+    /// Line of the note content, counted from 0.
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+    pub accepted: bool,
+}
+
+/// Compiles the find pattern; literal patterns match the text as typed.
+pub fn build_pattern(find: &str, regex: bool) -> Result<Regex, regex::Error> {
+    if regex {
+        Regex::new(find)
+    } else {
+        Regex::new(&regex::escape(find))
+    }
+}
+
+/// Every line of every note the replacement would change, all accepted.
+/// Regex replacements can refer to capture groups as `$1` or `${name}`.
+pub fn find_hits(
+    notes: &[Note],
+    pattern: &Regex,
+    replacement: &str,
+    regex: bool,
+) -> Vec<ReplaceHit> {
+    let mut hits = Vec::new();
+    for (index, note) in notes.iter().enumerate() {
+        for (line, text) in note.content.split('\n').enumerate() {
+            if !pattern.is_match(text) {
+                continue;
+            }
+            let after = if regex {
+                pattern.replace_all(text, replacement)
+            } else {
+                pattern.replace_all(text, NoExpand(replacement))
+            };
+            if after != text {
+                hits.push(ReplaceHit {
+                    index,
+                    line,
+                    before: text.to_string(),
+                    after: after.into_owned(),
+                    accepted: true,
+                });
+            }
+        }
+    }
+    hits
+}
+
+/// Looks for hits with the find and replace prompts.
+pub fn preview_replace(app: &mut App) -> bool {
+    app.search_error = None;
+    if app.input.value.is_empty() {
+        return false;
+    }
+    match build_pattern(&app.input.value, app.replace_regex) {
+        Ok(pattern) => {
+            app.replace_hits = find_hits(
+                &app.notes,
+                &pattern,
+                &app.replace_input.value,
+                app.replace_regex,
+            );
+            app.selected_hit = 0;
+            true
+        }
+        Err(err) => {
+            app.search_error = Some(regex_error_message(&err));
+            false
+        }
+    }
+}
+
+/// Writes the accepted hits to their notes.
+pub fn apply_replace(app: &mut App) {
+    let hits = std::mem::take(&mut app.replace_hits);
+    let mut saved = false;
+    let mut indexes: Vec<usize> = hits
+        .iter()
+        .filter(|h| h.accepted)
+        .map(|h| h.index)
+        .collect();
+    indexes.dedup();
+    for index in indexes {
+        let mut lines: Vec<String> = app.notes[index]
+            .content
+            .split('\n')
+            .map(str::to_string)
+            .collect();
+        let mut changed = false;
+        for hit in hits.iter().filter(|h| h.accepted && h.index == index) {
+            // Skip lines that changed since the preview was made.
+            if lines.get(hit.line) == Some(&hit.before) {
+                lines[hit.line] = hit.after.clone();
+                changed = true;
+            }
+        }
+        if changed {
+            app.notes[index].set_content(lines.join("\n"), app.config.title_source);
+            app.note_changed(index);
+            saved = true;
+        }
+    }
+    if saved {
+        save_notes(&app.notes);
+    }
+}
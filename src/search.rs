@@ -0,0 +1,332 @@
+use crate::app::App;
+use crate::config::{save_config, SavedSearch};
+use crate::index::{parse_query, QueryTerm};
+use crate::markdown::{parse_markdown_to_lines, rendered_line_at};
+use crate::notes::Note;
+use crate::query::{parse_filter, Filter};
+use regex::{Regex, RegexBuilder};
+use std::cmp::Reverse;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    /// Words looked up in the search index, ranked by relevance.
+    Ranked,
+    IgnoreCase,
+    WholeWord,
+    Regex,
+    /// A filter query like `tag:work is:open-todos updated:<7d`.
+    Query,
+}
+
+impl SearchMode {
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::IgnoreCase => SearchMode::WholeWord,
+            SearchMode::WholeWord => SearchMode::Regex,
+            SearchMode::Ranked => SearchMode::IgnoreCase,
+            SearchMode::Regex => SearchMode::Query,
+            SearchMode::Query => SearchMode::Ranked,
+        }
+    }
+
+    /// Next mode for searching within a note, which has nothing to rank.
+    pub fn next_in_note(self) -> Self {
+        match self {
+            SearchMode::IgnoreCase => SearchMode::WholeWord,
+            SearchMode::WholeWord => SearchMode::Regex,
+            _ => SearchMode::IgnoreCase,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Ranked => "ranked",
+            SearchMode::IgnoreCase => "ignore case",
+            SearchMode::WholeWord => "whole word",
+            SearchMode::Regex => "regex",
+            SearchMode::Query => "query",
+        }
+    }
+}
+
+pub struct SearchResult {
+    pub index: usize,
+    /// Line of the note content the match is on.
+    pub line: usize,
+    pub excerpt: String,
+}
+
+/// Compiles a query; plain queries ignore case, regexes are used as typed.
+pub fn build_matcher(query: &str, mode: SearchMode) -> Result<Regex, regex::Error> {
+    let pattern = match mode {
+        SearchMode::Ranked => ranked_pattern(&parse_query(query)),
+        SearchMode::IgnoreCase => regex::escape(query),
+        SearchMode::WholeWord => whole_word(query),
+        SearchMode::Regex => query.to_string(),
+        SearchMode::Query => parse_filter(query)
+            .map_or_else(|_| r"[^\s\S]".to_string(), |filter| filter_pattern(&filter)),
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(mode != SearchMode::Regex)
+        .build()
+}
+
+/// Pattern matching the query as a whole word. A word boundary only
+/// makes sense next to a word character, so `#tag` or `foo()` still match.
+fn whole_word(query: &str) -> String {
+    let boundary = |c: Option<char>| match c {
+        Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+        _ => "",
+    };
+    let start = boundary(query.chars().next());
+    let end = boundary(query.chars().last());
+    format!("{start}{}{end}", regex::escape(query))
+}
+
+/// Regex finding the words of an index query in a line of text.
+fn ranked_pattern(query: &[QueryTerm]) -> String {
+    let words: Vec<String> = query
+        .iter()
+        .map(|term| match term {
+            QueryTerm::Word(word) => format!(r"\b{}\b", regex::escape(word)),
+            QueryTerm::Prefix(prefix) => format!(r"\b{}\w*", regex::escape(prefix)),
+            QueryTerm::Phrase(words) => {
+                let words: Vec<String> = words.iter().map(|w| regex::escape(w)).collect();
+                format!(r"\b{}\b", words.join(r"[^\p{Alphabetic}\p{N}]+"))
+            }
+        })
+        .collect();
+    if words.is_empty() {
+        // Matches nothing.
+        return r"[^\s\S]".to_string();
+    }
+    words.join("|")
+}
+
+/// Regex finding the texts a filter query looks for.
+fn filter_pattern(filter: &Filter) -> String {
+    let texts: Vec<String> = filter.texts().into_iter().map(regex::escape).collect();
+    if texts.is_empty() {
+        return r"[^\s\S]".to_string();
+    }
+    texts.join("|")
+}
+
+/// Notes matching a filter query, most recently modified first.
+pub fn filter_notes(notes: &[Note], filter: &Filter) -> Vec<usize> {
+    let mut matches: Vec<usize> = notes
+        .iter()
+        .enumerate()
+        .filter(|(_, note)| filter.matches(note))
+        .map(|(index, _)| index)
+        .collect();
+    matches.sort_by_key(|&index| Reverse(notes[index].modified));
+    matches
+}
+
+/// Result for a whole note, pointing at its first line the matcher finds
+/// something on.
+fn note_result(notes: &[Note], index: usize, matcher: &Regex) -> SearchResult {
+    let content = &notes[index].content;
+    let (line, text) = content
+        .lines()
+        .enumerate()
+        .find(|(_, text)| matcher.is_match(text))
+        .unwrap_or((0, content.lines().next().unwrap_or("")));
+    SearchResult {
+        index,
+        line,
+        excerpt: text.trim().to_string(),
+    }
+}
+
+/// Notes found by the search index, best first, each with its first line
+/// containing one of the query words. Archived notes are only found in the
+/// archive view.
+pub fn ranked_search(app: &App, query: &str, matcher: &Regex) -> Vec<SearchResult> {
+    app.search_index
+        .search(&parse_query(query))
+        .into_iter()
+        .filter_map(|(id, _)| app.index_of(id))
+        .filter(|&index| app.notes[index].archived == app.show_archived)
+        .map(|index| note_result(&app.notes, index, matcher))
+        .collect()
+}
+
+/// Every line the matcher finds something on, in the archived notes or
+/// in the others.
+pub fn search_notes(notes: &[Note], matcher: &Regex, archived: bool) -> Vec<SearchResult> {
+    let mut results = Vec::new();
+    for (index, note) in notes.iter().enumerate() {
+        if note.archived != archived {
+            continue;
+        }
+        for (line, text) in note.content.lines().enumerate() {
+            if matcher.is_match(text) {
+                results.push(SearchResult {
+                    index,
+                    line,
+                    excerpt: text.trim().to_string(),
+                });
+            }
+        }
+    }
+    results
+}
+
+/// Last line of a regex error, which says what is wrong without the
+/// pattern excerpt.
+pub fn regex_error_message(err: &regex::Error) -> String {
+    let message = err.to_string();
+    let last = message.lines().last().unwrap_or("");
+    last.trim_start_matches("error: ").to_string()
+}
+
+/// Re-runs the search after the query or mode changed.
+pub fn update_search(app: &mut App) {
+    app.selected_match = 0;
+    app.search_results.clear();
+    app.search_error = None;
+    if app.input.value.is_empty() {
+        app.highlight = None;
+        return;
+    }
+    let filter = match app.search_mode {
+        SearchMode::Query => match parse_filter(&app.input.value) {
+            Ok(filter) => Some(filter),
+            Err(err) => {
+                app.highlight = None;
+                app.search_error = Some(err);
+                return;
+            }
+        },
+        _ => None,
+    };
+    let matcher = match &filter {
+        Some(filter) => RegexBuilder::new(&filter_pattern(filter))
+            .case_insensitive(true)
+            .build(),
+        None => build_matcher(&app.input.value, app.search_mode),
+    };
+    match matcher {
+        Ok(matcher) => {
+            app.search_results = match &filter {
+                Some(filter) => filter_notes(&app.notes, filter)
+                    .into_iter()
+                    .map(|index| note_result(&app.notes, index, &matcher))
+                    .collect(),
+                None if app.search_mode == SearchMode::Ranked => {
+                    ranked_search(app, &app.input.value, &matcher)
+                }
+                None => search_notes(&app.notes, &matcher, app.show_archived),
+            };
+            app.highlight = Some(matcher);
+        }
+        Err(err) => {
+            app.highlight = None;
+            app.search_error = Some(regex_error_message(&err));
+        }
+    }
+}
+
+/// Selects the note of the highlighted result and moves the preview to
+/// the matching line.
+pub fn open_search_result(app: &mut App) {
+    let Some(result) = app.search_results.get(app.selected_match) else {
+        return;
+    };
+    let (index, line) = (result.index, result.line);
+    let highlight = app.highlight.clone();
+    app.open_note(index);
+    app.highlight = highlight;
+
+    let target = rendered_line_at(&parse_markdown_to_lines(&app.notes[index].content), line);
+    app.selected_line = target;
+    app.note_scroll = target.saturating_sub(2) as u16;
+}
+
+/// Rendered lines of the selected note the highlight matches.
+pub fn matching_lines(app: &App) -> Vec<usize> {
+    let (Some(index), Some(matcher)) = (app.current_note(), &app.highlight) else {
+        return Vec::new();
+    };
+    parse_markdown_to_lines(&app.notes[index].content)
+        .iter()
+        .enumerate()
+        .filter(|(_, rendered)| matcher.is_match(&rendered.line.to_string()))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Moves `selected_line` to the next or previous matching line, wrapping
+/// around the note. With `from_current`, a match on the selected line
+/// counts as the next one.
+pub fn jump_to_match(app: &mut App, forward: bool, from_current: bool) {
+    let lines = matching_lines(app);
+    let current = app.selected_line;
+    let target = if forward {
+        lines
+            .iter()
+            .find(|&&line| line > current || (from_current && line == current))
+            .or(lines.first())
+    } else {
+        lines
+            .iter()
+            .rev()
+            .find(|&&line| line < current)
+            .or(lines.last())
+    };
+    if let Some(&target) = target {
+        app.selected_line = target;
+    }
+}
+
+/// Saves the pending query under the name typed into the prompt and
+/// shows its notes.
+pub fn save_search(app: &mut App) {
+    let name = app.input.value.trim();
+    if name.is_empty() {
+        return;
+    }
+    app.config.saved_searches.push(SavedSearch {
+        name: name.to_string(),
+        query: std::mem::take(&mut app.pending_query),
+    });
+    save_config(&app.config);
+    app.parse_saved_searches();
+    let position = app.config.saved_searches.len() - 1;
+    app.active_search = Some(position);
+    app.selected_search = Some(position);
+    app.selected_folder = None;
+    app.show_archived = false;
+    app.list_scroll = 0;
+}
+
+/// Shows the notes of the selected saved search, or the whole list again
+/// when it is already shown. A query that does not parse is not shown.
+pub fn toggle_saved_search(app: &mut App) {
+    if let Some(position) = app.selected_search {
+        if let Err(err) = &app.saved_filters[position] {
+            app.status = Some(format!("Invalid query: {}", err));
+            return;
+        }
+        app.active_search = match app.active_search {
+            Some(active) if active == position => None,
+            _ => Some(position),
+        };
+    }
+}
+
+pub fn delete_saved_search(app: &mut App) {
+    let Some(position) = app.selected_search.take() else {
+        return;
+    };
+    app.config.saved_searches.remove(position);
+    app.active_search = match app.active_search {
+        Some(active) if active == position => None,
+        Some(active) if active > position => Some(active - 1),
+        active => active,
+    };
+    save_config(&app.config);
+    app.parse_saved_searches();
+}
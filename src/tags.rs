@@ -0,0 +1,60 @@
+use crate::frontmatter::split_front_matter;
+use crate::notes::Note;
+use pulldown_cmark::{Event, Options, Parser};
+use std::collections::BTreeMap;
+
+/// Collects the `#tag` tokens of a note's body together with its front
+/// matter tags, lowercased and deduplicated. Tags inside code are ignored.
+pub fn extract_tags(content: &str) -> Vec<String> {
+    let (front_matter, body) = split_front_matter(content);
+    let mut tags: Vec<String> = front_matter
+        .map(|fm| fm.tags.iter().map(|t| t.to_lowercase()).collect())
+        .unwrap_or_default();
+
+    for event in Parser::new_ext(body, Options::all()) {
+        if let Event::Text(text) = event {
+            tags.extend(tags_in_text(&text));
+        }
+    }
+
+    let mut seen = Vec::new();
+    tags.retain(|t| {
+        if seen.contains(t) {
+            false
+        } else {
+            seen.push(t.clone());
+            true
+        }
+    });
+    tags
+}
+
+fn tags_in_text(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut prev = ' ';
+    for (i, c) in text.char_indices() {
+        if c == '#' && prev.is_whitespace() {
+            let rest = &text[i + 1..];
+            let len = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '-' || ch == '/'))
+                .unwrap_or(rest.len());
+            let tag = &rest[..len];
+            if tag.starts_with(|ch: char| ch.is_alphabetic()) {
+                tags.push(tag.to_lowercase());
+            }
+        }
+        prev = c;
+    }
+    tags
+}
+
+/// Counts how many active notes carry each tag, sorted by tag name.
+pub fn tag_index(notes: &[Note]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for note in notes.iter().filter(|note| !note.archived) {
+        for tag in &note.tags {
+            *counts.entry(tag.clone()).or_default() += 1;
+        }
+    }
+    counts.into_iter().collect()
+}
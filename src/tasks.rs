@@ -0,0 +1,56 @@
+use pulldown_cmark::{Event, Options, Parser};
+use std::ops::Range;
+
+/// A `- [ ]` list item found by the Markdown parser, so brackets in code
+/// or in the middle of a line are not taken for tasks.
+pub struct Task {
+    pub checked: bool,
+    /// Bytes of the `[ ]` or `[x]` marker in the content.
+    pub marker: Range<usize>,
+}
+
+pub fn extract_tasks(content: &str) -> Vec<Task> {
+    Parser::new_ext(content, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(checked) => Some(Task {
+                checked,
+                marker: range,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Number of open and done tasks.
+pub fn count_todos(content: &str) -> (usize, usize) {
+    extract_tasks(content)
+        .iter()
+        .fold((0, 0), |(open, closed), task| {
+            if task.checked {
+                (open, closed + 1)
+            } else {
+                (open + 1, closed)
+            }
+        })
+}
+
+/// How a task marker is written, and shown in the preview.
+pub fn marker_text(checked: bool) -> &'static str {
+    if checked {
+        "[x]"
+    } else {
+        "[ ]"
+    }
+}
+
+/// Content with the first task whose marker lies in `source` toggled, or
+/// `None` when there is no task there.
+pub fn toggle_task(content: &str, source: &Range<usize>) -> Option<String> {
+    let task = extract_tasks(content)
+        .into_iter()
+        .find(|task| source.contains(&task.marker.start))?;
+    let mut toggled = content.to_string();
+    toggled.replace_range(task.marker, marker_text(!task.checked));
+    Some(toggled)
+}
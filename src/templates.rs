@@ -0,0 +1,89 @@
+use crate::utils::data_dir;
+use chrono::Local;
+use std::path::PathBuf;
+use std::process::Command;
+
+pub const DEFAULT_TEMPLATE: &str = "# Title\n\n- [ ] New item";
+
+#[derive(Clone)]
+pub struct Template {
+    pub name: String,
+    pub content: String,
+}
+
+pub fn templates_dir() -> PathBuf {
+    let path = data_dir().join("templates");
+    std::fs::create_dir_all(&path).expect("Failed to create templates directory");
+    path
+}
+
+/// Loads every `*.md` file from the templates directory, sorted by name.
+/// Falls back to the built-in template when the directory is empty.
+pub fn load_templates() -> Vec<Template> {
+    let mut templates = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(templates_dir()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "md") {
+                if let (Some(name), Ok(content)) = (
+                    path.file_stem().and_then(|s| s.to_str()),
+                    std::fs::read_to_string(&path),
+                ) {
+                    templates.push(Template {
+                        name: name.to_string(),
+                        content,
+                    });
+                }
+            }
+        }
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if templates.is_empty() {
+        templates.push(Template {
+            name: "default".to_string(),
+            content: DEFAULT_TEMPLATE.to_string(),
+        });
+    }
+    templates
+}
+
+/// Expands the template variables and returns the resulting content along
+/// with the 1-based `(line, column)` of the `{{cursor}}` marker, if any.
+pub fn expand(template: &str) -> (String, Option<(usize, usize)>) {
+    let now = Local::now();
+    let mut content = template
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &now.format("%H:%M").to_string())
+        .replace("{{weekday}}", &now.format("%A").to_string());
+    if content.contains("{{clipboard}}") {
+        content = content.replace("{{clipboard}}", &read_clipboard());
+    }
+
+    let cursor = content.find("{{cursor}}").map(|idx| {
+        let before = &content[..idx];
+        let line = before.matches('\n').count() + 1;
+        let col = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+        (line, col)
+    });
+    let content = content.replace("{{cursor}}", "");
+
+    (content, cursor)
+}
+
+fn read_clipboard() -> String {
+    let commands: [(&str, &[&str]); 4] = [
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("xsel", &["--clipboard", "--output"]),
+        ("pbpaste", &[]),
+    ];
+    for (cmd, args) in commands {
+        if let Ok(output) = Command::new(cmd).args(args).output() {
+            if output.status.success() {
+                return String::from_utf8_lossy(&output.stdout).to_string();
+            }
+        }
+    }
+    String::new()
+}
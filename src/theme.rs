@@ -0,0 +1,12 @@
+use ratatui::style::Color;
+
+pub const RED: Color = Color::Rgb(210, 15, 57);
+pub const MAROON: Color = Color::Rgb(230, 69, 83);
+pub const PEACH: Color = Color::Rgb(254, 100, 11);
+pub const GREEN: Color = Color::Rgb(64, 160, 43);
+pub const TEAL: Color = Color::Rgb(23, 146, 153);
+pub const SAPPHIRE: Color = Color::Rgb(32, 159, 181);
+pub const TEXT: Color = Color::Rgb(76, 79, 105);
+pub const SUBTEXT1: Color = Color::Rgb(92, 95, 119);
+pub const SUBTEXT0: Color = Color::Rgb(108, 111, 133);
+pub const CRUST: Color = Color::Rgb(220, 224, 232);
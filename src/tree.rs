@@ -0,0 +1,109 @@
+use crate::notes::Note;
+use std::collections::{BTreeSet, HashSet};
+
+pub enum TreeRow {
+    /// A saved search, listed above the notes.
+    Search {
+        position: usize,
+        name: String,
+        /// Matching notes, or why the query could not be parsed.
+        count: Result<usize, String>,
+        active: bool,
+    },
+    Folder {
+        path: String,
+        name: String,
+        depth: usize,
+        expanded: bool,
+        count: usize,
+    },
+    Note {
+        index: usize,
+        depth: usize,
+    },
+}
+
+/// Normalizes a user supplied folder path: `/work//projects/` becomes
+/// `work/projects`, and the root folder is the empty string.
+pub fn normalize_folder(path: &str) -> String {
+    path.split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn parent_folder(path: &str) -> Option<&str> {
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.rsplit_once('/').map_or("", |(parent, _)| parent))
+    }
+}
+
+/// Whether `path` is `folder` itself or nested inside it.
+pub fn is_in_folder(path: &str, folder: &str) -> bool {
+    folder.is_empty()
+        || path == folder
+        || (path.starts_with(folder) && path[folder.len()..].starts_with('/'))
+}
+
+/// Flattens the folder tree of the `visible` notes into display rows,
+/// listing subfolders before the notes of each folder. Pinned notes are
+/// listed at the top, outside of their folders.
+pub fn build_tree(notes: &[Note], visible: &[usize], collapsed: &HashSet<String>) -> Vec<TreeRow> {
+    let (pinned, visible): (Vec<usize>, Vec<usize>) =
+        visible.iter().partition(|&&i| notes[i].is_pinned());
+    let visible = visible.as_slice();
+
+    let mut rows: Vec<TreeRow> = pinned
+        .into_iter()
+        .map(|index| TreeRow::Note { index, depth: 0 })
+        .collect();
+
+    let mut folders = BTreeSet::new();
+    for &i in visible {
+        let mut folder = notes[i].folder.as_str();
+        while let Some(parent) = parent_folder(folder) {
+            folders.insert(folder.to_string());
+            folder = parent;
+        }
+    }
+
+    push_folder(&mut rows, "", 0, notes, visible, &folders, collapsed);
+    rows
+}
+
+fn push_folder(
+    rows: &mut Vec<TreeRow>,
+    folder: &str,
+    depth: usize,
+    notes: &[Note],
+    visible: &[usize],
+    folders: &BTreeSet<String>,
+    collapsed: &HashSet<String>,
+) {
+    for path in folders
+        .iter()
+        .filter(|path| parent_folder(path) == Some(folder))
+    {
+        let expanded = !collapsed.contains(path);
+        rows.push(TreeRow::Folder {
+            path: path.clone(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            depth,
+            expanded,
+            count: visible
+                .iter()
+                .filter(|&&i| is_in_folder(&notes[i].folder, path))
+                .count(),
+        });
+        if expanded {
+            push_folder(rows, path, depth + 1, notes, visible, folders, collapsed);
+        }
+    }
+
+    for &i in visible.iter().filter(|&&i| notes[i].folder == folder) {
+        rows.push(TreeRow::Note { index: i, depth });
+    }
+}
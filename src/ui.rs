@@ -1,24 +1,25 @@
-use crate::app::{App, Focus, SelectedButton};
-use crate::notes::count_todos;
-use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use crate::app::{App, CaptureTarget, Focus, Mode, SelectedButton};
+use crate::calendar::{first_of_month, month_marks};
+use crate::editor::{Editor, LineEditor};
+use crate::fuzzy::find_matches;
+use crate::markdown::parse_markdown_to_lines;
+use crate::notes::is_duplicate_title;
+use crate::search::matching_lines;
+use crate::theme::{CRUST, GREEN, MAROON, PEACH, RED, SAPPHIRE, SUBTEXT0, SUBTEXT1, TEAL, TEXT};
+use crate::tree::TreeRow;
+use chrono::{Datelike, Days, Local, Weekday};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{
+        canvas::{Canvas, Line as CanvasLine},
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap,
+    },
     Frame,
 };
-
-const RED: Color = Color::Rgb(210, 15, 57);
-const MAROON: Color = Color::Rgb(230, 69, 83);
-const PEACH: Color = Color::Rgb(254, 100, 11);
-const GREEN: Color = Color::Rgb(64, 160, 43);
-const TEAL: Color = Color::Rgb(23, 146, 153);
-const SAPPHIRE: Color = Color::Rgb(32, 159, 181);
-const TEXT: Color = Color::Rgb(76, 79, 105);
-const SUBTEXT1: Color = Color::Rgb(92, 95, 119);
-const SUBTEXT0: Color = Color::Rgb(108, 111, 133);
-const CRUST: Color = Color::Rgb(220, 224, 232);
+use regex::Regex;
 
 pub fn ui(f: &mut Frame, app: &App) {
     let top_layout = Layout::default()
@@ -40,7 +41,7 @@ pub fn ui(f: &mut Frame, app: &App) {
 
     let main_layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
+        .constraints([Constraint::Min(1), Constraint::Length(4)].as_ref())
         .split(top_layout[1]);
 
     let notes_chunks = Layout::default()
@@ -48,31 +49,179 @@ pub fn ui(f: &mut Frame, app: &App) {
         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
         .split(main_layout[0]);
 
-    render_notes(f, app, &notes_chunks);
+    if let Mode::Graph = app.mode {
+        render_graph(f, app, main_layout[0]);
+    } else {
+        render_notes(f, app, &notes_chunks);
+    }
 
     if app.confirm_delete {
         render_confirmation_modal(f, app);
     }
 
-    let shortcuts_text = Line::from(vec![
-        Span::styled("[q]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Quit  "),
-        Span::styled("[c]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Create  "),
-        Span::styled("[e]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Edit  "),
-        Span::styled("[d]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Delete  "),
-        Span::styled("[h/l]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Focus Left/Right  "),
-        Span::styled("[j/k]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Move/Scroll  "),
-        Span::styled("[g/G]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Top/Bottom  "),
-        Span::styled("[space]", Style::default().fg(SAPPHIRE)),
-        Span::raw(" Toggle Todo"),
-    ]);
+    match app.mode {
+        Mode::SelectTemplate => render_template_picker(f, app),
+        Mode::Capture => render_capture_modal(f, app),
+        Mode::Rename => render_rename_modal(f, app),
+        Mode::SelectTag => render_tag_picker(f, app),
+        Mode::Calendar => render_calendar(f, app),
+        Mode::Find => render_finder(f, app),
+        Mode::Search => render_search(f, app),
+        Mode::Replace => render_replace_prompt(f, app),
+        Mode::ReplacePreview => render_replace_preview(f, app),
+        Mode::SaveSearch => render_input_modal(
+            f,
+            " Save Search As ",
+            &app.input,
+            vec![Line::from(Span::styled(
+                app.pending_query.clone(),
+                Style::default().fg(SUBTEXT0),
+            ))],
+        ),
+        Mode::NoteSearch => {
+            let title = format!(" / [{}] ", app.note_search_mode.label());
+            render_input_modal(f, &title, &app.input, Vec::new());
+        }
+        Mode::Move => render_input_modal(f, " Move To Folder ", &app.input, Vec::new()),
+        _ => {}
+    }
+
+    let shortcuts_text = match app.mode {
+        Mode::Normal => normal_shortcuts(&app.focus),
+        Mode::SelectTemplate => Line::from(vec![
+            Span::styled("[j/k]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Create From Template  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::SelectTag => Line::from(vec![
+            Span::styled("[j/k]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Filter By Tag  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::Graph => Line::from(vec![
+            Span::styled("[arrows/hjkl]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Open  "),
+            Span::styled("[c]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Center  "),
+            Span::styled("[+/-]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Hops  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Close"),
+        ]),
+        Mode::Calendar => Line::from(vec![
+            Span::styled("[hjkl/arrows]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[[/]]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Prev/Next Month  "),
+            Span::styled("[t]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Today  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Show Day's Notes  "),
+            Span::styled("[x]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Clear Filter  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Close"),
+        ]),
+        Mode::Find => Line::from(vec![
+            Span::styled("[up/down]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[ctrl-n/ctrl-p]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Next/Previous  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Open  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::Search => Line::from(vec![
+            Span::styled("[up/down]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[tab]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Change Mode  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Jump To Match  "),
+            Span::styled("[ctrl-s]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Save Query  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::Replace => Line::from(vec![
+            Span::styled("[tab]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Switch Field  "),
+            Span::styled("[ctrl-t]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Literal/Regex  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Preview  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::ReplacePreview => Line::from(vec![
+            Span::styled("[j/k]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[space]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Accept/Reject  "),
+            Span::styled("[a/n]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Accept/Reject All  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Apply  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Back"),
+        ]),
+        Mode::SaveSearch => Line::from(vec![
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Save  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::NoteSearch => Line::from(vec![
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Jump To First Match  "),
+            Span::styled("[tab]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Change Mode  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::Move => Line::from(vec![
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move (folders are separated by '/', empty for top level)  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::Capture => Line::from(vec![
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Capture  "),
+            Span::styled("[tab]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Change Target  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::Rename => Line::from(vec![
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Rename (empty to use first line)  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Cancel"),
+        ]),
+        Mode::Insert => Line::from(vec![
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Save & Leave Insert  "),
+            Span::styled("[arrows]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move Cursor  "),
+            Span::styled("[enter/backspace/del]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Split/Join Lines"),
+        ]),
+    };
 
+    let shortcuts_text = match &app.status {
+        Some(status) => Line::from(Span::styled(status.clone(), Style::default().fg(RED))),
+        None => shortcuts_text,
+    };
     let shortcuts = Paragraph::new(shortcuts_text)
         .wrap(Wrap { trim: true })
         .block(
@@ -84,6 +233,86 @@ pub fn ui(f: &mut Frame, app: &App) {
     f.render_widget(shortcuts, main_layout[1]);
 }
 
+fn normal_shortcuts(focus: &Focus) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled("[q]", Style::default().fg(SAPPHIRE)),
+        Span::raw(" Quit  "),
+        Span::styled("[c]", Style::default().fg(SAPPHIRE)),
+        Span::raw(" Create  "),
+        Span::styled("[e]", Style::default().fg(SAPPHIRE)),
+        Span::raw(" Edit  "),
+        Span::styled("[i]", Style::default().fg(SAPPHIRE)),
+        Span::raw(" Insert  "),
+        Span::styled("[a]", Style::default().fg(SAPPHIRE)),
+        Span::raw(" Capture  "),
+    ];
+    match focus {
+        Focus::Left => spans.extend([
+            Span::styled("[d]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Delete  "),
+            Span::styled("[r]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Rename  "),
+            Span::styled("[t]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Tags  "),
+            Span::styled("[m]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move  "),
+            Span::styled("[p]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Pin  "),
+            Span::styled("[s]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Sort  "),
+            Span::styled("[J/K]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Reorder  "),
+            Span::styled("[A/V]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Archive/View Archive  "),
+            Span::styled("[D]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Today  "),
+            Span::styled("[[/]]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Prev/Next Day  "),
+            Span::styled("[C]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Calendar  "),
+            Span::styled("[F]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Search  "),
+            Span::styled("[R]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Replace  "),
+            Span::styled("[h/l]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Collapse/Expand  "),
+            Span::styled("[j/k]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Move"),
+        ]),
+        Focus::Right => spans.extend([
+            Span::styled("[h]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Focus Notes  "),
+            Span::styled("[j/k]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Scroll  "),
+            Span::styled("[g/G]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Top/Bottom  "),
+            Span::styled("[space]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Toggle Todo  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Follow Link  "),
+            Span::styled("[/]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Search Note  "),
+            Span::styled("[n/N]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Next/Previous Match  "),
+            Span::styled("[B]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Backlinks  "),
+            Span::styled("[backspace]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Back"),
+        ]),
+        Focus::Backlinks => spans.extend([
+            Span::styled("[j/k]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Select  "),
+            Span::styled("[enter]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Open  "),
+            Span::styled("[esc]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Focus Preview  "),
+            Span::styled("[B]", Style::default().fg(SAPPHIRE)),
+            Span::raw(" Hide Backlinks"),
+        ]),
+    }
+    Line::from(spans)
+}
+
 fn render_confirmation_modal(f: &mut Frame, app: &App) {
     let area = centered_modal_area(f.area(), 40, 5);
 
@@ -98,7 +327,11 @@ fn render_confirmation_modal(f: &mut Frame, app: &App) {
         .style(Style::default().bg(TEXT));
     let text = Paragraph::new(vec![
         Line::from("Are you sure you"),
-        Line::from("want to delete this note?"),
+        Line::from(if app.selected_search.is_some() {
+            "want to delete this saved search?"
+        } else {
+            "want to delete this note?"
+        }),
     ])
     .block(block.clone())
     .alignment(ratatui::layout::Alignment::Center);
@@ -138,6 +371,480 @@ fn render_confirmation_modal(f: &mut Frame, app: &App) {
     f.render_widget(no_button, button_layout[1]);
 }
 
+fn render_template_picker(f: &mut Frame, app: &App) {
+    let height = (app.templates.len() as u16 + 2).min(f.area().height);
+    let area = centered_modal_area(f.area(), 40, height);
+
+    let items: Vec<ListItem> = app
+        .templates
+        .iter()
+        .enumerate()
+        .map(|(i, template)| {
+            let style = if i == app.selected_template {
+                Style::default()
+                    .fg(CRUST)
+                    .bg(TEXT)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(SUBTEXT1)
+            };
+            ListItem::new(Line::from(Span::styled(template.name.clone(), style)))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(" New Note From Template ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(PEACH)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(list, area);
+}
+
+fn render_tag_picker(f: &mut Frame, app: &App) {
+    let height = (app.tag_list.len() as u16 + 3).min(f.area().height);
+    let area = centered_modal_area(f.area(), 40, height);
+
+    let active = app.notes.iter().filter(|note| !note.archived).count();
+    let entries = std::iter::once(format!("All notes ({})", active)).chain(
+        app.tag_list
+            .iter()
+            .map(|(tag, count)| format!("#{} ({})", tag, count)),
+    );
+    let items: Vec<ListItem> = entries
+        .enumerate()
+        .map(|(i, label)| {
+            let style = if i == app.selected_tag {
+                Style::default()
+                    .fg(CRUST)
+                    .bg(TEXT)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(SUBTEXT1)
+            };
+            ListItem::new(Line::from(Span::styled(label, style)))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(" Tags ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(PEACH)),
+    );
+    let mut state = ListState::default().with_selected(Some(app.selected_tag));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_calendar(f: &mut Frame, app: &App) {
+    let date = app.calendar_date;
+    let first = first_of_month(date);
+    let marks = month_marks(&app.notes, date);
+    let today = Local::now().date_naive();
+
+    let mut lines = vec![Line::from(Span::styled(
+        " Mo  Tu  We  Th  Fr  Sa  Su",
+        Style::default().fg(SUBTEXT0),
+    ))];
+    let mut week = vec![Span::raw(
+        "    ".repeat(first.weekday().num_days_from_monday() as usize),
+    )];
+    for (day0, mark) in marks.iter().enumerate() {
+        let day = first + Days::new(day0 as u64);
+        let mut style = if mark.journal {
+            Style::default().fg(PEACH).add_modifier(Modifier::BOLD)
+        } else if mark.due {
+            Style::default().fg(RED)
+        } else if mark.activity {
+            Style::default().fg(GREEN)
+        } else {
+            Style::default().fg(SUBTEXT1)
+        };
+        if day == today {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        if day == date {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        let marker = if mark.due {
+            '!'
+        } else if mark.any() {
+            '•'
+        } else {
+            ' '
+        };
+        week.push(Span::styled(format!("{:>3}", day0 + 1), style));
+        week.push(Span::styled(
+            marker.to_string(),
+            style.fg(SUBTEXT0).remove_modifier(Modifier::REVERSED),
+        ));
+        if day.weekday() == Weekday::Sun {
+            lines.push(Line::from(std::mem::take(&mut week)));
+        }
+    }
+    if !week.is_empty() {
+        lines.push(Line::from(week));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(
+            "daily ",
+            Style::default().fg(PEACH).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("due! ", Style::default().fg(RED)),
+        Span::styled("edited", Style::default().fg(GREEN)),
+    ]));
+
+    let area = centered_modal_area(f.area(), 32, lines.len() as u16 + 2);
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(" {} ", date.format("%B %Y")))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(PEACH)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn render_capture_modal(f: &mut Frame, app: &App) {
+    let targets = [
+        (CaptureTarget::NewNote, "New note".to_string()),
+        (CaptureTarget::SelectedNote, "Selected note".to_string()),
+        (CaptureTarget::Inbox, app.config.inbox_title.clone()),
+    ];
+    let mut target_spans = vec![Span::styled("Into: ", Style::default().fg(SUBTEXT0))];
+    for (target, label) in targets {
+        let style = if target == app.capture_target {
+            Style::default()
+                .fg(CRUST)
+                .bg(TEXT)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(SUBTEXT1)
+        };
+        target_spans.push(Span::styled(format!(" {} ", label), style));
+        target_spans.push(Span::raw(" "));
+    }
+
+    render_input_modal(
+        f,
+        " Quick Capture ",
+        &app.input,
+        vec![Line::from(target_spans)],
+    );
+}
+
+fn render_rename_modal(f: &mut Frame, app: &App) {
+    let warning = if is_duplicate_title(app, &app.input.value) {
+        Line::from(Span::styled(
+            "⚠ Another note already has this title",
+            Style::default().fg(PEACH),
+        ))
+    } else {
+        Line::from("")
+    };
+    render_input_modal(f, " Rename Note ", &app.input, vec![warning]);
+}
+
+/// Draws a modal with a single-line input on top and `extra` lines below it.
+fn render_input_modal(f: &mut Frame, title: &str, input: &LineEditor, extra: Vec<Line>) {
+    let area = centered_modal_area(f.area(), 60, 3 + extra.len() as u16);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(PEACH));
+    let inner = block.inner(area);
+
+    let width = inner.width as usize;
+    let h_scroll = (input.cursor + 1).saturating_sub(width);
+
+    let mut lines = vec![Line::from(Span::styled(
+        input.value.chars().skip(h_scroll).collect::<String>(),
+        Style::default().fg(TEXT),
+    ))];
+    lines.extend(extra);
+
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+    f.set_cursor_position((inner.x + (input.cursor - h_scroll) as u16, inner.y));
+}
+
+fn render_finder(f: &mut Frame, app: &App) {
+    let matches = find_matches(app);
+    let limit = (f.area().height as usize).saturating_sub(8).clamp(1, 12);
+    let start = (app.selected_match + 1).saturating_sub(limit);
+
+    let mut lines: Vec<Line> = matches
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(limit)
+        .map(|(i, (index, positions))| {
+            let note = &app.notes[*index];
+            let base = if i == app.selected_match {
+                Style::default()
+                    .fg(CRUST)
+                    .bg(TEXT)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(SUBTEXT1)
+            };
+            let mut spans: Vec<Span> = note
+                .title
+                .chars()
+                .enumerate()
+                .map(|(pos, c)| {
+                    let style = if positions.contains(&pos) {
+                        base.fg(PEACH).add_modifier(Modifier::BOLD)
+                    } else {
+                        base
+                    };
+                    Span::styled(c.to_string(), style)
+                })
+                .collect();
+            if !note.folder.is_empty() {
+                spans.push(Span::styled(
+                    format!("  {}/", note.folder),
+                    Style::default().fg(SUBTEXT0),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No matching notes",
+            Style::default().fg(SUBTEXT0),
+        )));
+    }
+
+    let title = format!(" Find Note ({}/{}) ", matches.len(), app.notes.len());
+    render_input_modal(f, &title, &app.input, lines);
+}
+
+fn render_search(f: &mut Frame, app: &App) {
+    let limit = (f.area().height as usize).saturating_sub(8).clamp(1, 12);
+    let start = (app.selected_match + 1).saturating_sub(limit);
+    let match_style = Style::default().fg(PEACH).add_modifier(Modifier::BOLD);
+
+    let mut lines: Vec<Line> = app
+        .search_results
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(limit)
+        .map(|(i, result)| {
+            let selected = i == app.selected_match;
+            let base = if selected {
+                Style::default()
+                    .fg(CRUST)
+                    .bg(TEXT)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(SUBTEXT1)
+            };
+            let title_style = if selected { base } else { base.fg(SAPPHIRE) };
+            let mut spans = vec![Span::styled(
+                format!("{}:{} ", app.notes[result.index].title, result.line + 1),
+                title_style,
+            )];
+            let excerpt = Line::from(Span::styled(result.excerpt.clone(), base));
+            spans.extend(match &app.highlight {
+                Some(matcher) => highlight_matches(excerpt, matcher, match_style).spans,
+                None => excerpt.spans,
+            });
+            Line::from(spans)
+        })
+        .collect();
+    if let Some(err) = &app.search_error {
+        lines.push(Line::from(Span::styled(
+            format!("Invalid {}: {}", app.search_mode.label(), err),
+            Style::default().fg(RED),
+        )));
+    } else if app.search_results.is_empty() && !app.input.value.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No matches",
+            Style::default().fg(SUBTEXT0),
+        )));
+    }
+
+    let title = format!(
+        " Search [{}] ({} matches) ",
+        app.search_mode.label(),
+        app.search_results.len()
+    );
+    render_input_modal(f, &title, &app.input, lines);
+}
+
+/// Restyles the parts of a line the matcher finds, splitting spans where
+/// a match starts or ends inside them.
+fn highlight_matches(line: Line<'static>, matcher: &Regex, style: Style) -> Line<'static> {
+    let text = line.to_string();
+    let ranges: Vec<_> = matcher
+        .find_iter(&text)
+        .filter(|m| !m.is_empty())
+        .map(|m| m.range())
+        .collect();
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in line.spans {
+        let content = span.content.to_string();
+        let end = offset + content.len();
+        let mut cuts = vec![offset, end];
+        for range in &ranges {
+            cuts.extend(
+                [range.start, range.end]
+                    .into_iter()
+                    .filter(|&c| c > offset && c < end),
+            );
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for pair in cuts.windows(2) {
+            let piece = &content[pair[0] - offset..pair[1] - offset];
+            let matched = ranges
+                .iter()
+                .any(|r| r.start <= pair[0] && pair[1] <= r.end);
+            let piece_style = if matched {
+                span.style.patch(style)
+            } else {
+                span.style
+            };
+            spans.push(Span::styled(piece.to_string(), piece_style));
+        }
+        offset = end;
+    }
+    Line::from(spans)
+}
+
+fn render_replace_prompt(f: &mut Frame, app: &App) {
+    let area = centered_modal_area(f.area(), 60, 5);
+    let block = Block::default()
+        .title(if app.replace_regex {
+            " Replace [regex] "
+        } else {
+            " Replace [literal] "
+        })
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(PEACH));
+    let inner = block.inner(area);
+
+    let label_style = Style::default().fg(SUBTEXT0);
+    let fields = [
+        ("Find:    ", &app.input, !app.editing_replacement),
+        ("Replace: ", &app.replace_input, app.editing_replacement),
+    ];
+    let width = (inner.width as usize).saturating_sub(9);
+    let mut lines = Vec::new();
+    for (row, (label, input, active)) in fields.into_iter().enumerate() {
+        let h_scroll = (input.cursor + 1).saturating_sub(width);
+        lines.push(Line::from(vec![
+            Span::styled(label, label_style),
+            Span::styled(
+                input.value.chars().skip(h_scroll).collect::<String>(),
+                Style::default().fg(TEXT),
+            ),
+        ]));
+        if active {
+            f.set_cursor_position((
+                inner.x + 9 + (input.cursor - h_scroll) as u16,
+                inner.y + row as u16,
+            ));
+        }
+    }
+    lines.push(match &app.search_error {
+        Some(err) => Line::from(Span::styled(
+            format!("Invalid regex: {}", err),
+            Style::default().fg(RED),
+        )),
+        None if app.replace_regex => Line::from(Span::styled(
+            "Use $1 or ${name} for capture groups",
+            label_style,
+        )),
+        None => Line::from(""),
+    });
+
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn render_replace_preview(f: &mut Frame, app: &App) {
+    let screen = f.area();
+    let area = centered_modal_area(
+        screen,
+        screen.width.saturating_sub(8),
+        screen.height.saturating_sub(6),
+    );
+    let accepted = app.replace_hits.iter().filter(|hit| hit.accepted).count();
+    let block = Block::default()
+        .title(format!(
+            " Replace: {} of {} lines selected ",
+            accepted,
+            app.replace_hits.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(PEACH));
+
+    let mut lines = Vec::new();
+    let mut selected_row = 0;
+    for (i, hit) in app.replace_hits.iter().enumerate() {
+        if i == 0 || app.replace_hits[i - 1].index != hit.index {
+            lines.push(Line::from(Span::styled(
+                app.notes[hit.index].title.clone(),
+                Style::default().fg(SAPPHIRE).add_modifier(Modifier::BOLD),
+            )));
+        }
+        let selected = i == app.selected_hit;
+        if selected {
+            selected_row = lines.len();
+        }
+        let marker = if hit.accepted { "[x]" } else { "[ ]" };
+        let marker_style = if selected {
+            Style::default()
+                .fg(CRUST)
+                .bg(TEXT)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(SUBTEXT1)
+        };
+        let (before_style, after_style) = if hit.accepted {
+            (Style::default().fg(RED), Style::default().fg(GREEN))
+        } else {
+            (Style::default().fg(SUBTEXT0), Style::default().fg(SUBTEXT0))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} {:>4} ", marker, hit.line + 1), marker_style),
+            Span::styled(format!("- {}", hit.before.trim()), before_style),
+        ]));
+        lines.push(Line::from(vec![
+            Span::raw("         "),
+            Span::styled(format!("+ {}", hit.after.trim()), after_style),
+        ]));
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Nothing to replace",
+            Style::default().fg(SUBTEXT0),
+        )));
+    }
+
+    let height = block.inner(area).height as usize;
+    let scroll = (selected_row + 2).saturating_sub(height);
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .block(block),
+        area,
+    );
+}
+
 fn render_notes(f: &mut Frame, app: &App, chunks: &[Rect]) {
     let left_focus = matches!(app.focus, Focus::Left);
     let right_focus = matches!(app.focus, Focus::Right);
@@ -153,46 +860,135 @@ fn render_notes(f: &mut Frame, app: &App, chunks: &[Rect]) {
         Style::default().fg(TEAL)
     };
 
+    let visible = app.visible_notes();
+    let rows = app.tree_rows();
+    let selected_row = app.selected_row(&rows);
+    let note_position = rows
+        .iter()
+        .filter_map(|row| match row {
+            TreeRow::Note { index, .. } => Some(*index),
+            TreeRow::Folder { .. } | TreeRow::Search { .. } => None,
+        })
+        .position(|i| app.current_note() == Some(i))
+        .map_or(0, |position| position + 1);
+    let mut filter_label = match &app.tag_filter {
+        Some(tag) => format!(" #{}", tag),
+        None => String::new(),
+    };
+    if let Some(date) = app.date_filter {
+        filter_label.push_str(&format!(" {}", date.format("%Y-%m-%d")));
+    }
+    if let Some(search) = app
+        .active_search
+        .and_then(|i| app.config.saved_searches.get(i))
+    {
+        filter_label.push_str(&format!(" ⌕ {}", search.name));
+    }
+    let open_todos: usize = app
+        .notes
+        .iter()
+        .filter(|note| !note.archived)
+        .map(|note| note.todos.0)
+        .sum();
     let left_block = Block::default()
         .title(format!(
-            " Notes ({}/{}) ",
-            if app.notes.is_empty() {
-                0
+            " {} ({}/{}){} ",
+            if app.show_archived {
+                "Archived"
             } else {
-                app.selected_note + 1
+                "Notes"
             },
-            app.notes.len()
+            note_position,
+            visible.len(),
+            filter_label
+        ))
+        .title_bottom(format!(
+            " sort: {} · {} open todos ",
+            app.config.sort_mode.label(),
+            open_todos
         ))
         .borders(Borders::ALL)
         .border_style(left_block_style);
 
-    let mut note_items: Vec<ListItem> = app
-        .notes
+    let selected_style = Style::default()
+        .fg(CRUST)
+        .bg(TEXT)
+        .add_modifier(Modifier::BOLD);
+
+    let mut note_items: Vec<ListItem> = rows
         .iter()
         .enumerate()
-        .map(|(i, note)| {
-            let style = if i == app.selected_note {
-                Style::default()
-                    .fg(CRUST)
-                    .bg(TEXT)
-                    .add_modifier(Modifier::BOLD)
+        .map(|(row_index, row)| {
+            let style = if row_index == selected_row {
+                selected_style
             } else {
                 Style::default().fg(SUBTEXT1)
             };
 
-            let (open, closed) = count_todos(&note.content);
-            let mut display_title = String::new();
-            if open == 0 && closed > 0 {
-                display_title.push('✓');
-                display_title.push(' ');
-            }
-            display_title.push_str(&note.title);
+            match row {
+                TreeRow::Search {
+                    name,
+                    count,
+                    active,
+                    ..
+                } => {
+                    let style = match count {
+                        _ if row_index == selected_row => style,
+                        Ok(_) => style.fg(GREEN),
+                        Err(_) => style.fg(RED),
+                    };
+                    let count = match count {
+                        Ok(count) => count.to_string(),
+                        Err(err) => err.clone(),
+                    };
+                    ListItem::new(Line::from(Span::styled(
+                        format!("{} ⌕ {} ({})", if *active { '▾' } else { '▸' }, name, count),
+                        style,
+                    )))
+                }
+                TreeRow::Folder {
+                    name,
+                    depth,
+                    expanded,
+                    count,
+                    ..
+                } => {
+                    let style = if row_index == selected_row {
+                        style
+                    } else {
+                        style.fg(SAPPHIRE)
+                    };
+                    ListItem::new(Line::from(Span::styled(
+                        format!(
+                            "{}{} {}/ ({})",
+                            "  ".repeat(*depth),
+                            if *expanded { '▾' } else { '▸' },
+                            name,
+                            count
+                        ),
+                        style,
+                    )))
+                }
+                TreeRow::Note { index, depth } => {
+                    let note = &app.notes[*index];
+                    let (open, closed) = note.todos;
+                    let mut display_title = "  ".repeat(*depth);
+                    if note.is_pinned() {
+                        display_title.push_str("📌 ");
+                    }
+                    if open == 0 && closed > 0 {
+                        display_title.push('✓');
+                        display_title.push(' ');
+                    }
+                    display_title.push_str(&note.title);
 
-            if (open + closed) > 0 {
-                display_title.push_str(&format!(" ({}/{})", open, closed));
-            }
+                    if (open + closed) > 0 {
+                        display_title.push_str(&format!(" ({}/{})", open, closed));
+                    }
 
-            ListItem::new(Line::from(Span::styled(display_title, style)))
+                    ListItem::new(Line::from(Span::styled(display_title, style)))
+                }
+            }
         })
         .collect();
 
@@ -211,10 +1007,27 @@ fn render_notes(f: &mut Frame, app: &App, chunks: &[Rect]) {
     let notes_list = List::new(note_items).block(left_block);
     f.render_widget(notes_list, chunks[0]);
 
-    let right_block = Block::default()
-        .title(if !app.notes.is_empty() {
-            let curr_note = &app.notes[app.selected_note];
-            let (open, closed) = count_todos(&curr_note.content);
+    let (preview_area, backlinks_area) = if app.show_backlinks && app.current_note().is_some() {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(8)])
+            .split(chunks[1]);
+        (parts[0], Some(parts[1]))
+    } else {
+        (chunks[1], None)
+    };
+
+    let mut right_block = Block::default()
+        .title(if let Some(folder) = &app.selected_folder {
+            format!(" {}/ ", folder)
+        } else if let Some(search) = app
+            .selected_search
+            .and_then(|i| app.config.saved_searches.get(i))
+        {
+            format!(" ⌕ {} ", search.name)
+        } else if let Some(index) = app.current_note() {
+            let curr_note = &app.notes[index];
+            let (open, closed) = curr_note.todos;
             if (open + closed) > 0 {
                 format!(
                     " {} (Todos: {} open / {} done) ",
@@ -228,12 +1041,58 @@ fn render_notes(f: &mut Frame, app: &App, chunks: &[Rect]) {
         })
         .borders(Borders::ALL)
         .border_style(right_block_style);
+    if app.highlight.is_some() && app.current_note().is_some() {
+        let matches = matching_lines(app);
+        let current = matches
+            .iter()
+            .position(|&line| line == app.selected_line)
+            .map_or(0, |position| position + 1);
+        right_block = right_block
+            .title(Line::from(format!(" match {}/{} ", current, matches.len())).right_aligned());
+    }
 
-    if !app.notes.is_empty() {
-        let curr_note = &app.notes[app.selected_note];
+    if let Some(editor) = &app.editor {
+        render_editor(f, editor, right_block.title(" [INSERT] "), preview_area);
+    } else if let Some(folder) = &app.selected_folder {
+        let count = match &rows[selected_row] {
+            TreeRow::Folder { count, .. } => *count,
+            TreeRow::Note { .. } | TreeRow::Search { .. } => 0,
+        };
+        let paragraph = Paragraph::new(vec![
+            Line::from(format!("Notes in '{}': {}", folder, count)),
+            Line::from(""),
+            Line::from("Press 'l' to expand, 'h' to collapse, 'c' to create a note here."),
+        ])
+        .wrap(Wrap { trim: false })
+        .block(right_block);
+        f.render_widget(paragraph, preview_area);
+    } else if let Some(search) = app
+        .selected_search
+        .and_then(|i| app.config.saved_searches.get(i))
+    {
+        let count = match &rows[selected_row] {
+            TreeRow::Search {
+                count: Ok(count), ..
+            } => Line::from(format!("Matching notes: {}", count)),
+            TreeRow::Search {
+                count: Err(err), ..
+            } => Line::styled(format!("Invalid query: {}", err), Style::default().fg(RED)),
+            TreeRow::Note { .. } | TreeRow::Folder { .. } => Line::from(""),
+        };
+        let paragraph = Paragraph::new(vec![
+            Line::from(format!("Query: {}", search.query)),
+            count,
+            Line::from(""),
+            Line::from("Press 'l' or Enter to show its notes, 'h' to go back, 'd' to delete it."),
+        ])
+        .wrap(Wrap { trim: false })
+        .block(right_block);
+        f.render_widget(paragraph, preview_area);
+    } else if let Some(index) = app.current_note() {
+        let curr_note = &app.notes[index];
         let lines = parse_markdown_to_lines(&curr_note.content);
 
-        let height = right_block.inner(chunks[1]).height;
+        let height = right_block.inner(preview_area).height;
         let visible_height = height as usize;
 
         let line_count = lines.len();
@@ -248,7 +1107,7 @@ fn render_notes(f: &mut Frame, app: &App, chunks: &[Rect]) {
         let mut visible_lines = Vec::new();
         for (i, line) in lines.iter().enumerate() {
             if i >= start && i < end {
-                let mut styled_line = line.clone();
+                let mut styled_line = line.line.clone();
                 if i == selected_line {
                     styled_line = Line::from(
                         styled_line
@@ -264,6 +1123,13 @@ fn render_notes(f: &mut Frame, app: &App, chunks: &[Rect]) {
                             .collect::<Vec<Span>>(),
                     );
                 }
+                if let Some(matcher) = &app.highlight {
+                    styled_line = highlight_matches(
+                        styled_line,
+                        matcher,
+                        Style::default().fg(PEACH).add_modifier(Modifier::BOLD),
+                    );
+                }
                 visible_lines.push(styled_line);
             }
         }
@@ -271,93 +1137,151 @@ fn render_notes(f: &mut Frame, app: &App, chunks: &[Rect]) {
         let paragraph = Paragraph::new(visible_lines)
             .wrap(Wrap { trim: false })
             .block(right_block);
-        f.render_widget(paragraph, chunks[1]);
+        f.render_widget(paragraph, preview_area);
     } else {
-        let paragraph = Paragraph::new("Create a note with 'c'").block(right_block);
-        f.render_widget(paragraph, chunks[1]);
+        let hint = if app.show_archived {
+            "No archived notes, press 'V' to go back"
+        } else {
+            "Create a note with 'c'"
+        };
+        let paragraph = Paragraph::new(hint).block(right_block);
+        f.render_widget(paragraph, preview_area);
+    }
+
+    if let Some(area) = backlinks_area {
+        render_backlinks(f, app, area);
     }
 }
 
-fn parse_markdown_to_lines(input: &str) -> Vec<Line> {
-    let parser = Parser::new_ext(input, Options::all());
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
-    let mut in_heading = false;
-
-    for event in parser {
-        match event {
-            Event::Start(tag) => match tag {
-                Tag::Heading { .. } => {
-                    in_heading = true;
-                    if !current_line.is_empty() {
-                        lines.push(Line::from(current_line.clone()));
-                        current_line.clear();
-                    }
-                }
-                Tag::Item => {
-                    if !current_line.is_empty() {
-                        lines.push(Line::from(current_line.clone()));
-                        current_line.clear();
-                    }
-                    current_line.push_str("• ");
-                }
-                _ => {}
-            },
-            Event::End(tagend) => match tagend {
-                TagEnd::Heading { .. } => {
-                    if !current_line.is_empty() {
-                        lines.push(Line::from(Span::styled(
-                            current_line.clone(),
-                            Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-                        )));
-                        current_line.clear();
-                    }
-                    in_heading = false;
-                }
-                TagEnd::Item => {}
-                _ => {}
-            },
-            Event::Text(t) => {
-                current_line.push_str(&t);
-            }
-            Event::SoftBreak | Event::HardBreak => {
-                if !current_line.is_empty() {
-                    if in_heading {
-                        lines.push(Line::from(Span::styled(
-                            current_line.clone(),
-                            Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-                        )));
-                    } else {
-                        lines.push(Line::from(current_line.clone()));
-                    }
-                    current_line.clear();
-                } else {
-                    lines.push(Line::from(""));
-                }
+fn render_backlinks(f: &mut Frame, app: &App, area: Rect) {
+    let focused = matches!(app.focus, Focus::Backlinks);
+    let block_style = if focused {
+        Style::default().fg(PEACH).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(TEAL)
+    };
+    let backlinks = app.backlinks();
+    let block = Block::default()
+        .title(format!(" Backlinks ({}) ", backlinks.len()))
+        .borders(Borders::ALL)
+        .border_style(block_style);
+
+    if backlinks.is_empty() {
+        let paragraph = Paragraph::new(Span::styled(
+            "No notes link here",
+            Style::default().fg(SUBTEXT0),
+        ))
+        .block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = backlinks
+        .iter()
+        .map(|backlink| {
+            let title = app
+                .index_of(backlink.source)
+                .map_or("", |i| app.notes[i].title.as_str());
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}: ", title), Style::default().fg(SAPPHIRE)),
+                Span::styled(backlink.context.clone(), Style::default().fg(SUBTEXT1)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .fg(CRUST)
+            .bg(TEXT)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    if focused {
+        state.select(Some(app.selected_backlink.min(backlinks.len() - 1)));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_graph(f: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .title(format!(" Graph ({} hops) ", app.config.graph_hops))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(PEACH).add_modifier(Modifier::BOLD));
+    let Some(graph) = &app.graph else {
+        f.render_widget(Paragraph::new("No note selected").block(block), area);
+        return;
+    };
+
+    // Canvas coordinates are terminal cells, with the center note at the
+    // origin and room left for the labels at the edges.
+    let inner = block.inner(area);
+    let (half_width, half_height) = (inner.width as f64 / 2.0, inner.height as f64 / 2.0);
+    let (rx, ry) = ((half_width - 12.0).max(1.0), (half_height - 1.0).max(1.0));
+    let position = |i: usize| (graph.nodes[i].x * rx, graph.nodes[i].y * ry);
+
+    let title = if graph.nodes.len() == 1 {
+        format!(" {} has no links ", app.notes[graph.nodes[0].index].title)
+    } else {
+        format!(
+            " {} notes · {} links ",
+            graph.nodes.len(),
+            graph.edges.len()
+        )
+    };
+    let canvas = Canvas::default()
+        .block(block.title_bottom(title))
+        .marker(Marker::Braille)
+        .x_bounds([-half_width, half_width])
+        .y_bounds([-half_height, half_height])
+        .paint(|ctx| {
+            for &(from, to) in &graph.edges {
+                let ((x1, y1), (x2, y2)) = (position(from), position(to));
+                ctx.draw(&CanvasLine::new(x1, y1, x2, y2, SUBTEXT0));
             }
-            Event::TaskListMarker(checked) => {
-                if checked {
-                    current_line.push_str("[x] ");
-                } else {
-                    current_line.push_str("[ ] ");
+            ctx.layer();
+            for (i, node) in graph.nodes.iter().enumerate() {
+                let note = &app.notes[node.index];
+                let mut style = match node.depth {
+                    0 => Style::default().fg(PEACH).add_modifier(Modifier::BOLD),
+                    1 => Style::default().fg(SAPPHIRE),
+                    _ => Style::default().fg(SUBTEXT1),
+                };
+                if note.id == app.graph_selected {
+                    style = style.add_modifier(Modifier::REVERSED);
                 }
+                let label: String = format!("● {}", note.title).chars().take(24).collect();
+                let (x, y) = position(i);
+                let x = x - label.chars().count() as f64 / 2.0;
+                ctx.print(x, y, Span::styled(label, style));
             }
-            _ => {}
-        }
-    }
+        });
+    f.render_widget(canvas, area);
+}
 
-    if !current_line.is_empty() {
-        if in_heading {
-            lines.push(Line::from(Span::styled(
-                current_line,
-                Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-            )));
-        } else {
-            lines.push(Line::from(current_line));
-        }
-    }
+fn render_editor(f: &mut Frame, editor: &Editor, block: Block, area: Rect) {
+    let inner = block.inner(area);
+    let height = inner.height as usize;
+    let width = inner.width as usize;
+
+    let start = editor.visible_start(height);
+    let h_scroll = (editor.col + 1).saturating_sub(width);
+
+    let visible_lines: Vec<Line> = editor
+        .lines
+        .iter()
+        .skip(start)
+        .take(height)
+        .map(|l| Line::from(l.as_str()))
+        .collect();
+
+    let paragraph = Paragraph::new(visible_lines)
+        .scroll((0, h_scroll as u16))
+        .block(block);
+    f.render_widget(paragraph, area);
 
-    lines
+    f.set_cursor_position((
+        inner.x + (editor.col - h_scroll) as u16,
+        inner.y + (editor.row - start) as u16,
+    ));
 }
 
 fn centered_modal_area(screen: Rect, width: u16, height: u16) -> Rect {
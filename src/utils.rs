@@ -1,10 +1,30 @@
-use crate::app::{App, Focus, Mode, SelectedButton};
-use crate::notes::{create_note, delete_note, edit_note, toggle_todo};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::app::{App, CaptureTarget, Focus, Mode, SelectedButton};
+use crate::config::save_config;
+use crate::editor::LineEditor;
+use crate::fuzzy::{find_matches, open_match};
+use crate::graph::{move_graph_selection, open_graph_selection, refresh_graph};
+use crate::journal::{open_today, step_journal};
+use crate::links::{follow_link, go_back, open_backlink};
+use crate::markdown::parse_markdown_to_lines;
+use crate::notes::{
+    capture, create_note, delete_note, edit_note, finish_editing, move_note, rename_note,
+    reorder_note, start_editing, toggle_archive, toggle_pin, toggle_todo,
+};
+use crate::replace::{apply_replace, preview_replace};
+use crate::search::{
+    build_matcher, delete_saved_search, jump_to_match, open_search_result, save_search,
+    toggle_saved_search, update_search, SearchMode,
+};
+use crate::tags::tag_index;
+use crate::templates::load_templates;
+use crate::tree::parent_folder;
+use chrono::{Days, Local, Months};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::env;
 use std::path::PathBuf;
 
 pub fn handle_input(key: KeyEvent, app: &mut App) -> std::io::Result<bool> {
+    app.status = None;
     if app.confirm_delete {
         match key.code {
             KeyCode::Left | KeyCode::Char('h') => {
@@ -15,7 +35,11 @@ pub fn handle_input(key: KeyEvent, app: &mut App) -> std::io::Result<bool> {
             }
             KeyCode::Enter => match app.selected_button {
                 SelectedButton::Yes => {
-                    delete_note(app);
+                    if app.selected_search.is_some() {
+                        delete_saved_search(app);
+                    } else {
+                        delete_note(app);
+                    }
                     app.confirm_delete = false;
                 }
                 SelectedButton::No => {
@@ -24,28 +48,160 @@ pub fn handle_input(key: KeyEvent, app: &mut App) -> std::io::Result<bool> {
             },
             _ => {}
         }
+        app.sync_selection();
         return Ok(false);
     }
 
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
     match app.mode {
         Mode::Normal => match key.code {
             KeyCode::Char('q') => return Ok(true),
             KeyCode::Char('c') => {
-                create_note(app)?;
-            }
-            KeyCode::Char('d') => {
-                if !app.notes.is_empty() {
-                    app.confirm_delete = true;
-                    app.selected_button = SelectedButton::No;
+                app.templates = load_templates();
+                if app.templates.len() > 1 {
+                    app.selected_template = 0;
+                    app.mode = Mode::SelectTemplate;
+                } else {
+                    let template = app.templates[0].clone();
+                    create_note(app, &template)?;
                 }
             }
+            KeyCode::Char('d') if app.current_note().is_some() || app.selected_search.is_some() => {
+                app.confirm_delete = true;
+                app.selected_button = SelectedButton::No;
+            }
             KeyCode::Char('e') => {
                 edit_note(app)?;
             }
-            KeyCode::Char('h') => {
-                app.focus = Focus::Left;
+            KeyCode::Char('i') => {
+                start_editing(app);
+            }
+            KeyCode::Char('r') => {
+                if let Some(index) = app.current_note() {
+                    let note = &app.notes[index];
+                    let current = note.explicit_title.as_ref().unwrap_or(&note.title);
+                    app.input = LineEditor::new(current);
+                    app.mode = Mode::Rename;
+                }
+            }
+            KeyCode::Char('t') => {
+                app.tag_list = tag_index(&app.notes);
+                app.selected_tag = match &app.tag_filter {
+                    Some(tag) => app
+                        .tag_list
+                        .iter()
+                        .position(|(t, _)| t == tag)
+                        .map_or(0, |i| i + 1),
+                    None => 0,
+                };
+                app.mode = Mode::SelectTag;
+            }
+            KeyCode::Char('p') if ctrl => {
+                open_finder(app);
+            }
+            KeyCode::Char('F') => {
+                app.input = LineEditor::default();
+                update_search(app);
+                app.mode = Mode::Search;
+            }
+            KeyCode::Char('R') => {
+                app.input = LineEditor::default();
+                app.replace_input = LineEditor::default();
+                app.editing_replacement = false;
+                app.search_error = None;
+                app.mode = Mode::Replace;
+            }
+            KeyCode::Char('/') if matches!(app.focus, Focus::Left) => {
+                open_finder(app);
+            }
+            KeyCode::Char('/')
+                if matches!(app.focus, Focus::Right) && app.current_note().is_some() =>
+            {
+                app.input = LineEditor::default();
+                app.highlight = None;
+                app.mode = Mode::NoteSearch;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N')
+                if matches!(app.focus, Focus::Right) && app.highlight.is_some() =>
+            {
+                jump_to_match(app, key.code == KeyCode::Char('n'), false);
+                scroll_to_line(app);
+            }
+            KeyCode::Char('p') => {
+                toggle_pin(app);
+            }
+            KeyCode::Char('J') | KeyCode::Char('K') if matches!(app.focus, Focus::Left) => {
+                reorder_note(app, key.code == KeyCode::Char('J'));
+                scroll_to_selection(app);
+            }
+            KeyCode::Char('j') | KeyCode::Char('k')
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && matches!(app.focus, Focus::Left) =>
+            {
+                reorder_note(app, key.code == KeyCode::Char('j'));
+                scroll_to_selection(app);
+            }
+            KeyCode::Char('L') if app.current_note().is_some() => {
+                app.graph_center = app.selected_id;
+                app.graph_selected = app.selected_id;
+                refresh_graph(app);
+                app.mode = Mode::Graph;
+            }
+            KeyCode::Char('D') => {
+                open_today(app);
+                scroll_to_selection(app);
+            }
+            KeyCode::Char('C') => {
+                app.calendar_date = app.date_filter.unwrap_or(Local::now().date_naive());
+                app.mode = Mode::Calendar;
             }
-            KeyCode::Char('l') => {
+            KeyCode::Char('[') | KeyCode::Char(']') => {
+                step_journal(app, key.code == KeyCode::Char(']'));
+                scroll_to_selection(app);
+            }
+            KeyCode::Char('A') => {
+                toggle_archive(app);
+            }
+            KeyCode::Char('V') => {
+                app.show_archived = !app.show_archived;
+                app.selected_folder = None;
+                app.list_scroll = 0;
+            }
+            KeyCode::Char('s') => {
+                app.config.sort_mode = app.config.sort_mode.next();
+                save_config(&app.config);
+                scroll_to_selection(app);
+            }
+            KeyCode::Char('a') => {
+                app.input = LineEditor::default();
+                app.capture_target = CaptureTarget::NewNote;
+                app.mode = Mode::Capture;
+            }
+            KeyCode::Char('m') => {
+                if let Some(index) = app.current_note() {
+                    app.input = LineEditor::new(&app.notes[index].folder);
+                    app.mode = Mode::Move;
+                }
+            }
+            KeyCode::Char('h') => match app.focus {
+                Focus::Left => collapse_folder(app),
+                Focus::Right | Focus::Backlinks => app.focus = Focus::Left,
+            },
+            KeyCode::Char('l') => match app.focus {
+                Focus::Left => expand_folder(app),
+                Focus::Right | Focus::Backlinks => {}
+            },
+            KeyCode::Char('B') => match app.focus {
+                Focus::Backlinks => {
+                    app.show_backlinks = false;
+                    app.focus = Focus::Right;
+                }
+                _ => {
+                    app.show_backlinks = true;
+                    app.focus = Focus::Backlinks;
+                }
+            },
+            KeyCode::Esc if matches!(app.focus, Focus::Backlinks) => {
                 app.focus = Focus::Right;
             }
             KeyCode::Char('g') => {
@@ -56,8 +212,8 @@ pub fn handle_input(key: KeyEvent, app: &mut App) -> std::io::Result<bool> {
             }
             KeyCode::Char('G') => {
                 if let Focus::Right = app.focus {
-                    if !app.notes.is_empty() {
-                        let content = &app.notes[app.selected_note].content;
+                    if let Some(index) = app.current_note() {
+                        let content = &app.notes[index].content;
                         let line_count = parse_note_lines(content);
                         app.selected_line = line_count.saturating_sub(1);
 
@@ -75,31 +231,36 @@ pub fn handle_input(key: KeyEvent, app: &mut App) -> std::io::Result<bool> {
             }
             KeyCode::Char('j') => match app.focus {
                 Focus::Left => {
-                    if app.selected_note + 1 < app.notes.len() {
-                        app.selected_note += 1;
-                        adjust_list_scroll_down(app);
-                        app.note_scroll = 0;
-                        app.selected_line = 0;
+                    let rows = app.tree_rows();
+                    let position = app.selected_row(&rows);
+                    if position + 1 < rows.len() {
+                        app.select_row(&rows[position + 1]);
+                        adjust_list_scroll_down(app, position + 1);
                     }
                 }
                 Focus::Right => {
-                    if !app.notes.is_empty() {
-                        let content = &app.notes[app.selected_note].content;
+                    if let Some(index) = app.current_note() {
+                        let content = &app.notes[index].content;
                         let line_count = parse_note_lines(content);
-                        if app.selected_line + 1 < line_count - 1 {
+                        if app.selected_line + 1 < line_count {
                             app.selected_line += 1;
                             adjust_note_scroll_down(app, line_count);
                         }
                     }
                 }
+                Focus::Backlinks => {
+                    if app.selected_backlink + 1 < app.backlinks().len() {
+                        app.selected_backlink += 1;
+                    }
+                }
             },
             KeyCode::Char('k') => match app.focus {
                 Focus::Left => {
-                    if app.selected_note > 0 {
-                        app.selected_note -= 1;
-                        adjust_list_scroll_up(app);
-                        app.note_scroll = 0;
-                        app.selected_line = 0;
+                    let rows = app.tree_rows();
+                    let position = app.selected_row(&rows);
+                    if position > 0 {
+                        app.select_row(&rows[position - 1]);
+                        adjust_list_scroll_up(app, position - 1);
                     }
                 }
                 Focus::Right => {
@@ -108,30 +269,425 @@ pub fn handle_input(key: KeyEvent, app: &mut App) -> std::io::Result<bool> {
                         adjust_note_scroll_up(app);
                     }
                 }
+                Focus::Backlinks => {
+                    app.selected_backlink = app.selected_backlink.saturating_sub(1);
+                }
             },
             KeyCode::Char(' ') => {
                 toggle_todo(app);
             }
+            KeyCode::Enter if matches!(app.focus, Focus::Left) && app.selected_search.is_some() => {
+                toggle_saved_search(app);
+                app.list_scroll = 0;
+            }
+            KeyCode::Enter if matches!(app.focus, Focus::Right) => {
+                follow_link(app);
+                scroll_to_selection(app);
+            }
+            KeyCode::Enter if matches!(app.focus, Focus::Backlinks) => {
+                open_backlink(app);
+                scroll_to_selection(app);
+            }
+            KeyCode::Backspace => {
+                go_back(app);
+                scroll_to_selection(app);
+            }
+            _ => {}
+        },
+        Mode::SelectTemplate => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down
+                if app.selected_template + 1 < app.templates.len() =>
+            {
+                app.selected_template += 1;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.selected_template = app.selected_template.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                app.mode = Mode::Normal;
+                let template = app.templates[app.selected_template].clone();
+                create_note(app, &template)?;
+            }
+            _ => {}
+        },
+        Mode::SelectTag => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down if app.selected_tag < app.tag_list.len() => {
+                app.selected_tag += 1;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.selected_tag = app.selected_tag.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                app.tag_filter = match app.selected_tag {
+                    0 => None,
+                    i => Some(app.tag_list[i - 1].0.clone()),
+                };
+                if let Some(&first) = app.visible_notes().first() {
+                    app.select_note(first);
+                }
+                app.list_scroll = 0;
+                app.note_scroll = 0;
+                app.selected_line = 0;
+                app.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::Graph => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Left | KeyCode::Char('h') => move_graph_selection(app, -1.0, 0.0),
+            KeyCode::Right | KeyCode::Char('l') => move_graph_selection(app, 1.0, 0.0),
+            KeyCode::Up | KeyCode::Char('k') => move_graph_selection(app, 0.0, 1.0),
+            KeyCode::Down | KeyCode::Char('j') => move_graph_selection(app, 0.0, -1.0),
+            KeyCode::Char('c') => {
+                app.graph_center = app.graph_selected;
+                refresh_graph(app);
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') if app.config.graph_hops < 5 => {
+                app.config.graph_hops += 1;
+                save_config(&app.config);
+                refresh_graph(app);
+            }
+            KeyCode::Char('-') if app.config.graph_hops > 1 => {
+                app.config.graph_hops -= 1;
+                save_config(&app.config);
+                refresh_graph(app);
+            }
+            KeyCode::Enter => {
+                open_graph_selection(app);
+                scroll_to_selection(app);
+            }
+            _ => {}
+        },
+        Mode::Calendar => {
+            let date = app.calendar_date;
+            let moved = match key.code {
+                KeyCode::Left | KeyCode::Char('h') => date.checked_sub_days(Days::new(1)),
+                KeyCode::Right | KeyCode::Char('l') => date.checked_add_days(Days::new(1)),
+                KeyCode::Up | KeyCode::Char('k') => date.checked_sub_days(Days::new(7)),
+                KeyCode::Down | KeyCode::Char('j') => date.checked_add_days(Days::new(7)),
+                KeyCode::Char('[') => date.checked_sub_months(Months::new(1)),
+                KeyCode::Char(']') => date.checked_add_months(Months::new(1)),
+                KeyCode::Char('t') => Some(Local::now().date_naive()),
+                _ => None,
+            };
+            if let Some(moved) = moved {
+                app.calendar_date = moved;
+            } else {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        app.mode = Mode::Normal;
+                    }
+                    KeyCode::Char('x') => {
+                        app.date_filter = None;
+                        app.mode = Mode::Normal;
+                    }
+                    KeyCode::Enter => {
+                        let previous = app.date_filter.replace(date);
+                        // A day without notes would empty the list, so it
+                        // is not applied.
+                        match app.visible_notes().first() {
+                            Some(&first) => {
+                                app.select_note(first);
+                                app.list_scroll = 0;
+                                app.mode = Mode::Normal;
+                            }
+                            None => app.date_filter = previous,
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Mode::Find => match key.code {
+            KeyCode::Esc => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Down | KeyCode::Char('n') if key.code == KeyCode::Down || ctrl => {
+                if app.selected_match + 1 < find_matches(app).len() {
+                    app.selected_match += 1;
+                }
+            }
+            KeyCode::Up | KeyCode::Char('p') if key.code == KeyCode::Up || ctrl => {
+                app.selected_match = app.selected_match.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                open_match(app);
+                app.mode = Mode::Normal;
+                scroll_to_selection(app);
+            }
+            _ => {
+                app.input.handle_key(key);
+                app.selected_match = 0;
+            }
+        },
+        Mode::Search => match key.code {
+            KeyCode::Esc => {
+                app.highlight = None;
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Tab => {
+                app.search_mode = app.search_mode.next();
+                update_search(app);
+            }
+            KeyCode::Down | KeyCode::Char('n') if key.code == KeyCode::Down || ctrl => {
+                if app.selected_match + 1 < app.search_results.len() {
+                    app.selected_match += 1;
+                }
+            }
+            KeyCode::Up | KeyCode::Char('p') if key.code == KeyCode::Up || ctrl => {
+                app.selected_match = app.selected_match.saturating_sub(1);
+            }
+            KeyCode::Char('s') if ctrl && app.search_mode == SearchMode::Query => {
+                if app.search_error.is_none() && !app.input.value.trim().is_empty() {
+                    app.pending_query = app.input.value.trim().to_string();
+                    app.input = LineEditor::default();
+                    app.highlight = None;
+                    app.mode = Mode::SaveSearch;
+                }
+            }
+            KeyCode::Enter => {
+                open_search_result(app);
+                app.mode = Mode::Normal;
+                scroll_to_selection(app);
+            }
+            _ => {
+                app.input.handle_key(key);
+                update_search(app);
+            }
+        },
+        Mode::NoteSearch => match key.code {
+            KeyCode::Esc => {
+                app.highlight = None;
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                jump_to_match(app, true, true);
+                scroll_to_line(app);
+                app.mode = Mode::Normal;
+            }
+            _ => {
+                if key.code == KeyCode::Tab {
+                    app.note_search_mode = app.note_search_mode.next_in_note();
+                } else {
+                    app.input.handle_key(key);
+                }
+                app.highlight = Some(&app.input.value)
+                    .filter(|query| !query.is_empty())
+                    .and_then(|query| build_matcher(query, app.note_search_mode).ok());
+            }
+        },
+        Mode::SaveSearch => match key.code {
+            KeyCode::Esc => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                save_search(app);
+                app.mode = Mode::Normal;
+            }
+            _ => app.input.handle_key(key),
+        },
+        Mode::Replace => match key.code {
+            KeyCode::Esc => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                app.editing_replacement = !app.editing_replacement;
+            }
+            KeyCode::Char('t') if ctrl => {
+                app.replace_regex = !app.replace_regex;
+                app.search_error = None;
+            }
+            KeyCode::Enter => {
+                if preview_replace(app) {
+                    app.mode = Mode::ReplacePreview;
+                }
+            }
+            _ if app.editing_replacement => app.replace_input.handle_key(key),
+            _ => app.input.handle_key(key),
+        },
+        Mode::ReplacePreview => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.mode = Mode::Replace;
+            }
+            KeyCode::Char('j') | KeyCode::Down if app.selected_hit + 1 < app.replace_hits.len() => {
+                app.selected_hit += 1;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.selected_hit = app.selected_hit.saturating_sub(1);
+            }
+            KeyCode::Char(' ') => {
+                if let Some(hit) = app.replace_hits.get_mut(app.selected_hit) {
+                    hit.accepted = !hit.accepted;
+                }
+                if app.selected_hit + 1 < app.replace_hits.len() {
+                    app.selected_hit += 1;
+                }
+            }
+            KeyCode::Char('a') | KeyCode::Char('n') => {
+                let accepted = key.code == KeyCode::Char('a');
+                for hit in app.replace_hits.iter_mut() {
+                    hit.accepted = accepted;
+                }
+            }
+            KeyCode::Enter => {
+                apply_replace(app);
+                app.mode = Mode::Normal;
+            }
             _ => {}
         },
+        Mode::Move => match key.code {
+            KeyCode::Esc => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                move_note(app);
+                app.mode = Mode::Normal;
+                scroll_to_selection(app);
+            }
+            _ => app.input.handle_key(key),
+        },
+        Mode::Capture => match key.code {
+            KeyCode::Esc => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Tab => {
+                app.capture_target = match app.capture_target {
+                    CaptureTarget::NewNote => CaptureTarget::SelectedNote,
+                    CaptureTarget::SelectedNote => CaptureTarget::Inbox,
+                    CaptureTarget::Inbox => CaptureTarget::NewNote,
+                };
+            }
+            KeyCode::Enter => {
+                if capture(app) {
+                    app.mode = Mode::Normal;
+                }
+            }
+            _ => app.input.handle_key(key),
+        },
+        Mode::Rename => match key.code {
+            KeyCode::Esc => {
+                app.mode = Mode::Normal;
+            }
+            KeyCode::Enter => {
+                rename_note(app);
+                app.mode = Mode::Normal;
+            }
+            _ => app.input.handle_key(key),
+        },
+        Mode::Insert => match key.code {
+            KeyCode::Esc => {
+                finish_editing(app);
+                scroll_to_line(app);
+            }
+            _ => {
+                if let Some(editor) = app.editor.as_mut() {
+                    editor.handle_key(key);
+                }
+            }
+        },
     }
+    app.sync_selection();
     Ok(false)
 }
 
+fn open_finder(app: &mut App) {
+    app.input = LineEditor::default();
+    app.selected_match = 0;
+    app.mode = Mode::Find;
+}
+
+/// Collapses the selected folder, or the folder enclosing the selection.
+/// On a shown saved search, goes back to the whole list.
+fn collapse_folder(app: &mut App) {
+    if app.selected_search.is_some() {
+        if app.active_search == app.selected_search {
+            toggle_saved_search(app);
+        }
+        return;
+    }
+    let folder = match &app.selected_folder {
+        Some(folder) if !app.collapsed_folders.contains(folder) => Some(folder.clone()),
+        Some(folder) => parent_folder(folder)
+            .filter(|parent| !parent.is_empty())
+            .map(str::to_string),
+        None => app
+            .current_note()
+            .map(|index| app.notes[index].folder.clone())
+            .filter(|folder| !folder.is_empty()),
+    };
+
+    if let Some(folder) = folder {
+        app.collapsed_folders.insert(folder.clone());
+        app.selected_folder = Some(folder);
+        let rows = app.tree_rows();
+        let position = app.selected_row(&rows);
+        adjust_list_scroll_up(app, position);
+    }
+}
+
+/// Expands a collapsed folder or shows a saved search, steps into an
+/// expanded one, or moves the focus to the preview when a note is selected.
+fn expand_folder(app: &mut App) {
+    if app.selected_search.is_some() && app.active_search != app.selected_search {
+        toggle_saved_search(app);
+        app.list_scroll = 0;
+        return;
+    }
+    match app.selected_folder.clone() {
+        Some(folder) if app.collapsed_folders.contains(&folder) => {
+            app.collapsed_folders.remove(&folder);
+        }
+        None if app.selected_search.is_none() => app.focus = Focus::Right,
+        _ => {
+            let rows = app.tree_rows();
+            let position = app.selected_row(&rows);
+            if position + 1 < rows.len() {
+                app.select_row(&rows[position + 1]);
+                adjust_list_scroll_down(app, position + 1);
+            }
+        }
+    }
+}
+
+/// Number of lines the preview shows for `content`.
 fn parse_note_lines(content: &str) -> usize {
-    content.lines().count()
+    parse_markdown_to_lines(content).len()
 }
 
-fn adjust_list_scroll_down(app: &mut App) {
+/// Scrolls the preview so the selected line is visible.
+fn scroll_to_line(app: &mut App) {
+    if let Some(index) = app.current_note() {
+        let line_count = parse_note_lines(&app.notes[index].content);
+        adjust_note_scroll_up(app);
+        adjust_note_scroll_down(app, line_count);
+    }
+}
+
+fn scroll_to_selection(app: &mut App) {
+    let rows = app.tree_rows();
+    let position = app.selected_row(&rows);
+    adjust_list_scroll_up(app, position);
+    adjust_list_scroll_down(app, position);
+}
+
+fn adjust_list_scroll_down(app: &mut App, position: usize) {
     let visible_height = 10;
-    if app.selected_note >= app.list_scroll + visible_height {
-        app.list_scroll = app.selected_note.saturating_sub(visible_height - 1);
+    if position >= app.list_scroll + visible_height {
+        app.list_scroll = position.saturating_sub(visible_height - 1);
     }
 }
 
-fn adjust_list_scroll_up(app: &mut App) {
-    if app.selected_note < app.list_scroll {
-        app.list_scroll = app.selected_note;
+fn adjust_list_scroll_up(app: &mut App, position: usize) {
+    if position < app.list_scroll {
+        app.list_scroll = position;
     }
 }
 
@@ -152,9 +708,13 @@ fn adjust_note_scroll_up(app: &mut App) {
     }
 }
 
-pub fn notes_path() -> PathBuf {
+pub fn data_dir() -> PathBuf {
     let home = env::var("HOME").expect("HOME not set");
     let path = PathBuf::from(home).join(".local/share/yana");
     std::fs::create_dir_all(&path).expect("Failed to create notes directory");
-    path.join("notes.json")
+    path
+}
+
+pub fn notes_path() -> PathBuf {
+    data_dir().join("notes.json")
 }